@@ -4,63 +4,392 @@ use anyhow::{Context, Result, anyhow};
 
 use advent_of_code::template::RunType;
 
-pub fn parse_input(input: &str) -> Result<Vec<i32>> {
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("line {line}, column {column}: expected 'L' or 'R' but found {found:?}")]
+    Direction {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    #[error("line {line}, column {column}: expected a rotation distance but found {text:?}")]
+    Distance {
+        line: usize,
+        column: usize,
+        text: String,
+    },
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<i32>, ParseError> {
     let mut out = Vec::new();
-    for line in input.lines() {
-        let (lr, n_str) = line.split_at(1);
-
-        let n: i32 = n_str.parse().context("failed to parse input int")?;
-        if lr == "L" {
-            out.push(-n);
-        } else if lr == "R" {
-            out.push(n);
-        } else {
-            return Err(anyhow!("got unexpected l/r value: {lr}"));
-        }
+    for (idx, raw_line) in input.lines().enumerate() {
+        let line = idx + 1;
+        let trimmed = raw_line.trim_start();
+        // Columns are 1-based and counted in chars so they line up in an editor
+        let column = raw_line.chars().count() - trimmed.chars().count() + 1;
+
+        let mut chars = trimmed.trim_end().chars();
+        let lr = match chars.next() {
+            Some(lr) => lr,
+            None => continue,
+        };
+        let n_str = chars.as_str();
+
+        let sign = match lr {
+            'L' => -1,
+            'R' => 1,
+            'l' | 'r' => {
+                eprintln!(
+                    "warning: line {line}, column {column}: lowercase '{lr}' treated as '{}'",
+                    lr.to_ascii_uppercase()
+                );
+                if lr == 'l' { -1 } else { 1 }
+            }
+            found => {
+                return Err(ParseError::Direction {
+                    line,
+                    column,
+                    found,
+                });
+            }
+        };
+
+        let n: i32 = n_str
+            .parse::<u32>()
+            .ok()
+            .and_then(|n| n.try_into().ok())
+            .ok_or_else(|| ParseError::Distance {
+                line,
+                column: column + 1,
+                text: n_str.to_string(),
+            })?;
+        out.push(sign * n);
     }
 
     Ok(out)
 }
 
-pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
-    let mut out = 0;
-    let mut current = 50;
-    for n in parse_input(input).context("failed to parse input")? {
-        current = (current + n) % 100;
-        if current == 0 {
-            out += 1;
-        }
+// Default dial used by the puzzle: 100 positions starting at 50.
+const DIAL_SIZE: i32 = 100;
+const DIAL_START: i32 = 50;
+
+// Set to any value to print the step-by-step trace of every rotation.
+const TRACE_ENV: &str = "AOC_DAY1_TRACE";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub rotation: i32,
+    pub position: i32,
+    pub zero_hits: u32,
+}
+
+impl std::fmt::Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lr = if self.rotation < 0 { 'L' } else { 'R' };
+        let n = self.rotation.unsigned_abs();
+        write!(
+            f,
+            "{lr}{n:<6} -> position={:<4} zero_hits={}",
+            self.position, self.zero_hits
+        )
     }
-    Ok(Some(out))
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
-    let mut out: u32 = 0;
-    let mut current = 50;
-    for n in parse_input(input).context("failed to parse input")? {
-        let start = current;
-        let full_rotations: i32 = if n == 0 { 0 } else { n / 100 };
-
-        let n_single = n - 100 * full_rotations;
-        let current_no_wrap = current + n_single;
-        current = (current + n_single) % 100;
-        if current < 0 {
-            current += 100;
+#[derive(Debug, Clone)]
+pub struct Dial {
+    size: i32,
+    position: i32,
+}
+
+impl Dial {
+    pub fn new(size: i32, start: i32) -> Result<Self> {
+        if size <= 0 {
+            return Err(anyhow!("dial size must be positive, got {size}"));
+        }
+        if !(0..size).contains(&start) {
+            return Err(anyhow!("start {start} is outside of dial with size {size}"));
         }
+        Ok(Self {
+            size,
+            position: start,
+        })
+    }
+
+    pub fn position(&self) -> i32 {
+        self.position
+    }
 
-        let mut delta: u32 = 0;
-        delta += full_rotations.unsigned_abs();
-        if current == 0 || (start != 0 && current != current_no_wrap) {
-            delta += 1;
+    // Number of clicks during a rotation of `n` that land on 0, including the final one. Done in
+    // i64 since a distance near i32::MAX plus the position doesn't fit in an i32.
+    fn zero_hits(&self, n: i32) -> u32 {
+        // Going left is the same as going right from the mirrored position
+        let distance = if n < 0 {
+            (self.size - self.position) % self.size
+        } else {
+            self.position
+        };
+        let hits = (i64::from(distance) + i64::from(n).abs()) / i64::from(self.size);
+        hits.try_into()
+            .expect("distance < size, so at most 2^31 hits")
+    }
+
+    pub fn rotate(&mut self, n: i32) -> Step {
+        let zero_hits = self.zero_hits(n);
+        self.position = (i64::from(self.position) + i64::from(n))
+            .rem_euclid(i64::from(self.size))
+            .try_into()
+            .expect("always smaller than size");
+        Step {
+            rotation: n,
+            position: self.position,
+            zero_hits,
+        }
+    }
+
+    pub fn run(&mut self, rotations: &[i32]) -> Vec<Step> {
+        rotations.iter().map(|n| self.rotate(*n)).collect()
+    }
+}
+
+fn run(input: &str) -> Result<Vec<Step>> {
+    let rotations = parse_input(input).context("failed to parse input")?;
+    let mut dial = Dial::new(DIAL_SIZE, DIAL_START)?;
+    let steps = dial.run(&rotations);
+
+    if std::env::var_os(TRACE_ENV).is_some() {
+        for step in &steps {
+            println!("{step}");
         }
-        out += delta;
     }
-    Ok(Some(out))
+    Ok(steps)
 }
 
+pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
+    let steps = run(input)?;
+    Ok(Some(steps.iter().filter(|s| s.position == 0).count() as u32))
+}
+
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
+    let steps = run(input)?;
+    let hits = steps
+        .iter()
+        .try_fold(0_u32, |acc, s| acc.checked_add(s.zero_hits))
+        .context("total zero hits does not fit in a u32")?;
+    Ok(Some(hits))
+}
+
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_1 {
     use super::*;
+    use crate::test_rng::Rng;
+
+    #[test]
+    fn test_parse_input() -> anyhow::Result<()> {
+        assert_eq!(
+            parse_input("L68\n\nR48  \n  l5\nr60\n")?,
+            vec![-68, 48, -5, 60]
+        );
+
+        assert_eq!(
+            parse_input("L1\nX5"),
+            Err(ParseError::Direction {
+                line: 2,
+                column: 1,
+                found: 'X'
+            })
+        );
+        assert_eq!(
+            parse_input("L1\n  ÄB"),
+            Err(ParseError::Direction {
+                line: 2,
+                column: 3,
+                found: 'Ä'
+            })
+        );
+        assert_eq!(
+            parse_input("R\n"),
+            Err(ParseError::Distance {
+                line: 1,
+                column: 2,
+                text: String::new()
+            })
+        );
+        assert_eq!(
+            parse_input("R1\nL-5"),
+            Err(ParseError::Distance {
+                line: 2,
+                column: 2,
+                text: "-5".to_string()
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dial_steps() -> anyhow::Result<()> {
+        let mut dial = Dial::new(100, 50)?;
+        let steps: Vec<(i32, u32)> = dial
+            .run(&[-68, -30, 48, 1000, -1000, 0])
+            .into_iter()
+            .map(|s| (s.position, s.zero_hits))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![(82, 1), (52, 0), (0, 1), (0, 10), (0, 10), (0, 0)]
+        );
+
+        let mut dial = Dial::new(10, 0)?;
+        assert_eq!(dial.rotate(-1).zero_hits, 0);
+        assert_eq!(dial.rotate(1).zero_hits, 1);
+        assert_eq!(dial.rotate(-21).zero_hits, 2);
+        assert_eq!(dial.position(), 9);
+
+        assert!(Dial::new(0, 0).is_err());
+        assert!(Dial::new(10, 10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dial_extreme_rotations() -> anyhow::Result<()> {
+        let mut dial = Dial::new(100, 50)?;
+        let step = dial.rotate(i32::MAX);
+        assert_eq!((step.position, step.zero_hits), (97, 21474836));
+
+        let mut dial = Dial::new(100, 50)?;
+        let step = dial.rotate(-i32::MAX);
+        assert_eq!((step.position, step.zero_hits), (3, 21474836));
+
+        let mut dial = Dial::new(1, 0)?;
+        assert_eq!(dial.rotate(i32::MIN).zero_hits, 1 << 31);
+        assert!(dial.rotate(i32::MIN).to_string().starts_with("L2147483648"));
+
+        let mut dial = Dial::new(i32::MAX, i32::MAX - 1)?;
+        assert_eq!(dial.rotate(i32::MAX).zero_hits, 1);
+        assert_eq!(dial.position(), i32::MAX - 1);
+
+        assert_eq!(parse_input("R2147483647")?, vec![i32::MAX]);
+        assert!(parse_input("R2147483648").is_err());
+        assert_eq!(
+            part_two("R2147483647\nL2147483647\n", RunType::Example)?,
+            Some(42949672)
+        );
+        Ok(())
+    }
+
+    // Reference implementation that moves the dial one click at a time.
+    fn oracle(size: i32, start: i32, rotations: &[i32]) -> Vec<(i32, u32)> {
+        let mut position = start;
+        let mut out = Vec::new();
+        for n in rotations {
+            let mut zero_hits = 0;
+            for _ in 0..n.abs() {
+                position = (position + n.signum()).rem_euclid(size);
+                if position == 0 {
+                    zero_hits += 1;
+                }
+            }
+            out.push((position, zero_hits));
+        }
+        out
+    }
+
+    fn fast(size: i32, start: i32, rotations: &[i32]) -> Vec<(i32, u32)> {
+        let mut dial = Dial::new(size, start).expect("valid dial");
+        dial.run(rotations)
+            .into_iter()
+            .map(|s| (s.position, s.zero_hits))
+            .collect()
+    }
+
+    fn agrees(size: i32, start: i32, rotations: &[i32]) -> bool {
+        fast(size, start, rotations) == oracle(size, start, rotations)
+    }
+
+    // Greedily drop rotations and move everything towards 0 while the failure still reproduces.
+    fn shrink(size: i32, mut start: i32, mut rotations: Vec<i32>) -> (i32, Vec<i32>) {
+        let smaller = |n: i32| {
+            [0, n / 2, n - n.signum()]
+                .into_iter()
+                .filter(move |s| *s != n)
+        };
+
+        let mut progress = true;
+        while progress {
+            progress = false;
+            if let Some(candidate) = smaller(start).find(|s| !agrees(size, *s, &rotations)) {
+                start = candidate;
+                progress = true;
+            }
+            for i in (0..rotations.len()).rev() {
+                let mut candidate = rotations.clone();
+                candidate.remove(i);
+                if !agrees(size, start, &candidate) {
+                    rotations = candidate;
+                    progress = true;
+                }
+            }
+            for i in 0..rotations.len() {
+                for value in smaller(rotations[i]) {
+                    let mut candidate = rotations.clone();
+                    candidate[i] = value;
+                    if !agrees(size, start, &candidate) {
+                        rotations = candidate;
+                        progress = true;
+                        break;
+                    }
+                }
+            }
+        }
+        (start, rotations)
+    }
+
+    fn random_rotation(rng: &mut Rng, size: i32) -> i32 {
+        let size = size as u64;
+        let magnitude = match rng.below(4) {
+            0 => 0,
+            1 => size * rng.below(5),
+            2 => rng.below(size),
+            _ => rng.below(5 * size),
+        } as i32;
+        if rng.below(2) == 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    #[test]
+    fn test_dial_matches_oracle() {
+        let mut rng = Rng::new(0x2025_0001);
+        for _ in 0..2000 {
+            let size = if rng.below(2) == 0 {
+                100
+            } else {
+                rng.below(20) as i32 + 1
+            };
+            // Start on 0 often, it is the easiest place to get wrong
+            let start = if rng.below(3) == 0 {
+                0
+            } else {
+                rng.below(size as u64) as i32
+            };
+            let len = rng.below(20) as usize;
+            let rotations: Vec<i32> = (0..len).map(|_| random_rotation(&mut rng, size)).collect();
+
+            if !agrees(size, start, &rotations) {
+                let (start, minimal) = shrink(size, start, rotations);
+                panic!(
+                    "dial disagrees with oracle for size={size} start={start} rotations={minimal:?}: fast={:?} oracle={:?}",
+                    fast(size, start, &minimal),
+                    oracle(size, start, &minimal),
+                );
+            }
+        }
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
@@ -2,37 +2,90 @@ advent_of_code::solution!(2);
 
 use anyhow::{Context, Result, anyhow};
 use aoc_lib::parse::preamble::*;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 use advent_of_code::template::RunType;
 
-pub fn make_pattern(n: u64, copies: u32) -> u64 {
-    let mut out = 0;
-    let digits = n.ilog10() + 1;
-    let mult = 10_u64.pow(digits);
+// Unsigned integers the pattern generators can work with. Everything that can overflow goes
+// through the checked_* methods so wide inputs fail loudly instead of wrapping in release builds.
+pub trait PatternInt:
+    Copy + Ord + std::fmt::Debug + std::fmt::Display + From<u8> + std::ops::Div<Output = Self>
+{
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_pow(self, exp: u32) -> Option<Self>;
+    fn ilog10(self) -> u32;
+}
+
+macro_rules! impl_pattern_int {
+    ($($t:ty),*) => {
+        $(
+            impl PatternInt for $t {
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$t>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, other)
+                }
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, other)
+                }
+                fn checked_pow(self, exp: u32) -> Option<Self> {
+                    <$t>::checked_pow(self, exp)
+                }
+                fn ilog10(self) -> u32 {
+                    <$t>::ilog10(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_pattern_int!(u64, u128);
+
+fn pow10<T: PatternInt>(exp: u32) -> Option<T> {
+    T::from(10).checked_pow(exp)
+}
+
+fn num_digits<T: PatternInt>(n: T) -> u32 {
+    let digits = if n == T::from(0) { 0 } else { n.ilog10() };
+    digits + 1
+}
+
+pub fn make_pattern<T: PatternInt>(n: T, copies: u32) -> Result<T> {
+    let overflow = || anyhow!("pattern {n} x {copies} overflows");
+
+    let mut out = T::from(0);
+    let mult: T = pow10(num_digits(n)).ok_or_else(overflow)?;
     for _ in 0..copies {
-        out *= mult;
-        out += n;
+        out = out
+            .checked_mul(mult)
+            .and_then(|out| out.checked_add(n))
+            .ok_or_else(overflow)?;
     }
-    out
+    Ok(out)
 }
 
-pub fn make_patterns_in_range(start: u64, end: u64, pattern_len: u32) -> Vec<u64> {
+pub fn make_patterns_in_range<T: PatternInt>(start: T, end: T, pattern_len: u32) -> Vec<T> {
     let mut out = Vec::new();
-    let mut last: u64;
 
-    let start_digits = if start == 0 {
-        0
+    // Skip straight to the first block that can produce a pattern >= start, for wide ranges
+    // counting up from 10^n would take forever.
+    let start_digits = num_digits(start);
+    let block_digits = start_digits.div_ceil(pattern_len);
+    let mut i: T = if start_digits.is_multiple_of(pattern_len) {
+        let shift: T = pow10(start_digits - block_digits).expect("smaller than start");
+        start / shift
     } else {
-        start.ilog10() / pattern_len
+        pow10(block_digits - 1).expect("smaller than start")
     };
-    let mut i: u64 = 10_u64.pow(start_digits);
-    loop {
-        last = make_pattern(i, pattern_len);
+    // Anything that overflows is also past the end of the range
+    while let Ok(last) = make_pattern(i, pattern_len) {
         if last > end {
             break;
         }
-        i += 1;
+        i = i.checked_add(T::from(1)).expect("always smaller than last");
         if last >= start {
             out.push(last);
         }
@@ -40,13 +93,13 @@ pub fn make_patterns_in_range(start: u64, end: u64, pattern_len: u32) -> Vec<u64
     out
 }
 
-pub fn make_doubles(start: u64, end: u64) -> Vec<u64> {
+pub fn make_doubles<T: PatternInt>(start: T, end: T) -> Vec<T> {
     make_patterns_in_range(start, end, 2)
 }
 
-pub fn make_patterns(start: u64, end: u64, out: &mut BTreeSet<u64>) {
-    let start_digits = if start == 0 { 0 } else { start.ilog10() } + 1;
-    let end_digits = if end == 0 { 0 } else { end.ilog10() } + 1;
+pub fn make_patterns<T: PatternInt>(start: T, end: T, out: &mut BTreeSet<T>) {
+    let start_digits = num_digits(start);
+    let end_digits = num_digits(end);
 
     for len in 2..=end_digits {
         if start_digits.is_multiple_of(len) || end_digits.is_multiple_of(len) {
@@ -57,8 +110,256 @@ pub fn make_patterns(start: u64, end: u64, out: &mut BTreeSet<u64>) {
     }
 }
 
-pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
-    let data: Vec<Vec<(u64, u64)>> = parse_input(
+fn checked_sum<T: PatternInt>(values: impl IntoIterator<Item = T>) -> Result<T> {
+    values.into_iter().try_fold(T::from(0), |acc, v| {
+        acc.checked_add(v)
+            .ok_or_else(|| anyhow!("sum overflowed adding {v} to {acc}"))
+    })
+}
+
+// Set to any value to print every invalid ID and the pattern(s) that produced it.
+const EXPLAIN_ENV: &str = "AOC_DAY2_EXPLAIN";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation<T> {
+    pub id: T,
+    // (block, copies) for every way of building id, shortest block first
+    pub patterns: Vec<(T, u32)>,
+}
+
+impl<T: PatternInt> std::fmt::Display for Explanation<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|(block, copies)| format!("\"{block}\" x {copies}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{} = {patterns}", self.id)?;
+        if self.patterns.len() > 1 {
+            write!(f, " (multiple patterns)")?;
+        }
+        Ok(())
+    }
+}
+
+pub fn explain_range<T: PatternInt>(start: T, end: T) -> Vec<Explanation<T>> {
+    let mut found: BTreeMap<T, Vec<(T, u32)>> = BTreeMap::new();
+    for copies in (2..=num_digits(end)).rev() {
+        for id in make_patterns_in_range(start, end, copies) {
+            let block_digits = num_digits(id) / copies;
+            let shift: T = pow10(num_digits(id) - block_digits).expect("smaller than id");
+            found.entry(id).or_default().push((id / shift, copies));
+        }
+    }
+    found
+        .into_iter()
+        .map(|(id, patterns)| Explanation { id, patterns })
+        .collect()
+}
+
+// Lists every input range as given, with IDs that more than one range contains flagged. The
+// footer counts each ID once so it matches part two even when ranges overlap.
+pub fn render_explanation(ranges: &[(u128, u128)]) -> Result<String> {
+    use std::fmt::Write;
+
+    let explained: Vec<Vec<Explanation<u128>>> = ranges
+        .iter()
+        .map(|(start, end)| explain_range(*start, *end))
+        .collect();
+    // Indexes of the ranges each ID is in
+    let mut owners: BTreeMap<u128, Vec<usize>> = BTreeMap::new();
+    for (idx, explanations) in explained.iter().enumerate() {
+        for explanation in explanations {
+            owners.entry(explanation.id).or_default().push(idx);
+        }
+    }
+
+    let mut out = String::new();
+    let mut rows = vec![[
+        "range".to_string(),
+        "ids".to_string(),
+        "multi".to_string(),
+        "shared".to_string(),
+        "sum".to_string(),
+    ]];
+    for (idx, ((start, end), explanations)) in ranges.iter().zip(&explained).enumerate() {
+        let sum = checked_sum(explanations.iter().map(|e| e.id))?;
+        let multi = explanations.iter().filter(|e| e.patterns.len() > 1).count();
+        let mut shared = 0;
+
+        writeln!(out, "{start}-{end}:")?;
+        for explanation in explanations {
+            write!(out, "    {explanation}")?;
+            let others: Vec<String> = owners[&explanation.id]
+                .iter()
+                .filter(|other| **other != idx)
+                .map(|other| format!("{}-{}", ranges[*other].0, ranges[*other].1))
+                .collect();
+            if !others.is_empty() {
+                shared += 1;
+                write!(out, " (also in {})", others.join(", "))?;
+            }
+            writeln!(out)?;
+        }
+        rows.push([
+            format!("{start}-{end}"),
+            explanations.len().to_string(),
+            multi.to_string(),
+            shared.to_string(),
+            sum.to_string(),
+        ]);
+    }
+    rows.push([
+        "total".to_string(),
+        owners.len().to_string(),
+        String::new(),
+        String::new(),
+        checked_sum(owners.keys().copied())?.to_string(),
+    ]);
+
+    let widths: Vec<usize> = (0..5)
+        .map(|col| rows.iter().map(|row| row[col].len()).max().unwrap_or(0))
+        .collect();
+    writeln!(out)?;
+    for [range, ids, multi, shared, sum] in rows {
+        writeln!(
+            out,
+            "{range:<w0$}  {ids:>w1$}  {multi:>w2$}  {shared:>w3$}  {sum:>w4$}",
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+        )?;
+    }
+    Ok(out)
+}
+
+// Möbius function, only ever called with small arguments (digit counts)
+fn mobius(mut n: u32) -> i128 {
+    let mut out = 1;
+    let mut p = 2;
+    while p * p <= n {
+        if n.is_multiple_of(p) {
+            n /= p;
+            if n.is_multiple_of(p) {
+                return 0;
+            }
+            out = -out;
+        }
+        p += 1;
+    }
+    if n > 1 {
+        out = -out;
+    }
+    out
+}
+
+// Sum of every number in start..=end with exactly `total_len` digits that is a `block_len`
+// digit block repeated to fill it. These are block * 1{0..}1{0..}1 so as the block grows
+// the numbers grow too and we can sum them as an arithmetic series.
+fn sum_periodic<T: PatternInt>(start: T, end: T, total_len: u32, block_len: u32) -> Result<T> {
+    let one = T::from(1);
+    let overflow = || anyhow!("sum of {block_len} digit patterns in {start}-{end} overflows");
+
+    // Every total_len digit number fits if 10^total_len doesn't
+    let lo = match pow10::<T>(total_len - 1) {
+        Some(lo) => std::cmp::max(start, lo),
+        None => return Ok(T::from(0)),
+    };
+    let hi = match pow10::<T>(total_len) {
+        Some(limit) => std::cmp::min(end, limit.checked_sub(one).expect("limit is at least 1")),
+        None => end,
+    };
+    if lo > hi {
+        return Ok(T::from(0));
+    }
+
+    // 1 followed by the block shifted along, if it doesn't fit then no pattern does either
+    let shift: T = pow10(block_len).expect("block is shorter than a number that fits");
+    let mut mult = T::from(0);
+    for _ in 0..total_len / block_len {
+        match mult.checked_mul(shift).and_then(|m| m.checked_add(one)) {
+            Some(next) => mult = next,
+            None => return Ok(T::from(0)),
+        }
+    }
+
+    let ceil_lo = if (lo / mult).checked_mul(mult) == Some(lo) {
+        lo / mult
+    } else {
+        (lo / mult).checked_add(one).ok_or_else(overflow)?
+    };
+    let block_lo = std::cmp::max(ceil_lo, pow10(block_len - 1).expect("smaller than shift"));
+    let block_hi = std::cmp::min(
+        hi / mult,
+        shift.checked_sub(one).expect("shift is at least 1"),
+    );
+    if block_lo > block_hi {
+        return Ok(T::from(0));
+    }
+
+    // (block_lo + block_hi) * count / 2, halving whichever factor is even first
+    let two = T::from(2);
+    let count = (block_hi.checked_sub(block_lo))
+        .and_then(|c| c.checked_add(one))
+        .ok_or_else(overflow)?;
+    let ends = block_lo.checked_add(block_hi).ok_or_else(overflow)?;
+    let blocks = if (count / two).checked_mul(two) == Some(count) {
+        (count / two).checked_mul(ends)
+    } else {
+        (ends / two).checked_mul(count)
+    };
+    blocks
+        .and_then(|blocks| blocks.checked_mul(mult))
+        .ok_or_else(overflow)
+}
+
+// Sum of every ID in start..=end that is a block repeated exactly twice, what make_doubles would
+// produce but without generating anything.
+pub fn sum_doubles_in_range<T: PatternInt>(start: T, end: T) -> Result<T> {
+    let mut out = T::from(0);
+    if start > end {
+        return Ok(out);
+    }
+    for total_len in (2..=num_digits(end)).step_by(2) {
+        out = checked_sum([out, sum_periodic(start, end, total_len, total_len / 2)?])?;
+    }
+    Ok(out)
+}
+
+// Sums what make_patterns would produce but without generating anything. A number is invalid if
+// it repeats with some period that is a proper divisor of its length. Summing every such period
+// counts numbers like 111111 several times so we use inclusion–exclusion over the divisors, the
+// coefficient of each divisor ends up being the Möbius function. The terms added and taken away
+// are kept apart so this works for any unsigned type.
+pub fn sum_patterns_in_range<T: PatternInt>(start: T, end: T) -> Result<T> {
+    let (mut added, mut removed) = (T::from(0), T::from(0));
+    if start > end {
+        return Ok(added);
+    }
+    for total_len in 2..=num_digits(end) {
+        for copies in 2..=total_len {
+            if !total_len.is_multiple_of(copies) {
+                continue;
+            }
+            // -mobius is the coefficient, so -1 is added and +1 taken away
+            let sum = || sum_periodic(start, end, total_len, total_len / copies);
+            match mobius(copies) {
+                0 => {}
+                -1 => added = checked_sum([added, sum()?])?,
+                _ => removed = checked_sum([removed, sum()?])?,
+            }
+        }
+    }
+    Ok(added
+        .checked_sub(removed)
+        .expect("inclusion–exclusion never goes negative"))
+}
+
+fn parse_ranges(input: &str) -> Result<Vec<(u128, u128)>> {
+    let data: Vec<Vec<(u128, u128)>> = parse_input(
         LineSplitter,
         SplitDelim(ParseTuple2(ParseFromStr, ParseFromStr, "-"), ","),
         input,
@@ -71,59 +372,75 @@ pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::
             data.len()
         ));
     }
-    let data = data.into_iter().next().unwrap();
+    Ok(data.into_iter().next().unwrap())
+}
+
+pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u128>, anyhow::Error> {
+    let data = parse_ranges(input)?;
 
-    let mut out: u64 = 0;
+    let mut out: u128 = 0;
     for (x, y) in data {
-        let doubles = make_doubles(x, y);
-        out += doubles.iter().sum::<u64>();
+        let doubles = sum_doubles_in_range(x, y)?;
+        out = checked_sum([out, doubles])?;
     }
     Ok(Some(out))
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
-    let data: Vec<Vec<(u64, u64)>> = parse_input(
-        LineSplitter,
-        SplitDelim(ParseTuple2(ParseFromStr, ParseFromStr, "-"), ","),
-        input,
-    )
-    .context("failed to parse input")?;
+// Sorts the ranges and merges any that overlap, so no ID is counted twice.
+pub fn merge_ranges(mut ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    ranges.sort();
+    let mut merged: Vec<(u128, u128)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => {
+                *last_end = std::cmp::max(*last_end, end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
 
-    if data.len() != 1 {
-        return Err(anyhow!(
-            "invalid input, found {} lines, expected 1",
-            data.len()
-        ));
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u128>, anyhow::Error> {
+    let data = parse_ranges(input)?;
+
+    if std::env::var_os(EXPLAIN_ENV).is_some() {
+        print!(
+            "{}",
+            render_explanation(&data).context("failed to explain ranges")?
+        );
     }
-    let data = data.into_iter().next().unwrap();
 
-    let mut out: BTreeSet<u64> = BTreeSet::new();
-    for (x, y) in data {
-        make_patterns(x, y, &mut out);
+    let merged = merge_ranges(data);
+
+    let mut out: u128 = 0;
+    for (x, y) in merged {
+        out = checked_sum([out, sum_patterns_in_range(x, y)?])?;
     }
-    Ok(Some(out.iter().sum::<u64>()))
+    Ok(Some(out))
 }
 
 #[cfg(test)]
 mod tests_day_2 {
     use super::*;
+    use itertools::Itertools;
 
     #[test]
     fn test_make_doubles() -> anyhow::Result<()> {
-        assert_eq!(make_doubles(0, 10), vec![]);
+        assert_eq!(make_doubles(0_u64, 10), vec![]);
         assert_eq!(
-            make_doubles(0, 100),
+            make_doubles(0_u64, 100),
             vec![11, 22, 33, 44, 55, 66, 77, 88, 99]
         );
-        assert_eq!(make_doubles(50, 100), vec![55, 66, 77, 88, 99]);
-        assert_eq!(make_doubles(99, 100), vec![99]);
-        assert_eq!(make_doubles(100, 100), vec![]);
+        assert_eq!(make_doubles(50_u64, 100), vec![55, 66, 77, 88, 99]);
+        assert_eq!(make_doubles(99_u64, 100), vec![99]);
+        assert_eq!(make_doubles(100_u64, 100), vec![]);
         assert_eq!(
-            make_doubles(0, 1000),
+            make_doubles(0_u64, 1000),
             vec![11, 22, 33, 44, 55, 66, 77, 88, 99]
         );
         assert_eq!(
-            make_doubles(0, 10000),
+            make_doubles(0_u64, 10000),
             vec![
                 11, 22, 33, 44, 55, 66, 77, 88, 99, 1010, 1111, 1212, 1313, 1414, 1515, 1616, 1717,
                 1818, 1919, 2020, 2121, 2222, 2323, 2424, 2525, 2626, 2727, 2828, 2929, 3030, 3131,
@@ -135,7 +452,7 @@ mod tests_day_2 {
             ]
         );
         assert_eq!(
-            make_doubles(7777, 10000),
+            make_doubles(7777_u64, 10000),
             vec![
                 7777, 7878, 7979, 8080, 8181, 8282, 8383, 8484, 8585, 8686, 8787, 8888, 8989, 9090,
                 9191, 9292, 9393, 9494, 9595, 9696, 9797, 9898, 9999
@@ -147,32 +464,217 @@ mod tests_day_2 {
 
     #[test]
     fn test_make_pattern() -> anyhow::Result<()> {
-        assert_eq!(make_pattern(1, 3), 111);
-        assert_eq!(make_pattern(12, 3), 121212);
-        assert_eq!(make_pattern(123, 3), 123123123);
+        assert_eq!(make_pattern(1_u64, 3)?, 111);
+        assert_eq!(make_pattern(12_u64, 3)?, 121212);
+        assert_eq!(make_pattern(123_u64, 3)?, 123123123);
+
+        assert!(make_pattern(1234567890_u64, 2).is_ok());
+        assert!(make_pattern(12345678901_u64, 2).is_err());
+        assert_eq!(
+            make_pattern(12345678901_u128, 2)?,
+            1234567890112345678901_u128
+        );
+        assert!(make_pattern(u128::MAX / 2, 2).is_err());
 
         Ok(())
     }
 
     #[test]
     fn test_make_patterns_in_range() -> anyhow::Result<()> {
-        assert_eq!(make_patterns_in_range(50, 100, 2), vec![55, 66, 77, 88, 99]);
         assert_eq!(
-            make_patterns_in_range(50, 1000, 2),
+            make_patterns_in_range(50_u64, 100, 2),
             vec![55, 66, 77, 88, 99]
         );
         assert_eq!(
-            make_patterns_in_range(50, 1000, 3),
+            make_patterns_in_range(50_u64, 1000, 2),
+            vec![55, 66, 77, 88, 99]
+        );
+        assert_eq!(
+            make_patterns_in_range(50_u64, 1000, 3),
             vec![111, 222, 333, 444, 555, 666, 777, 888, 999]
         );
         assert_eq!(
-            make_patterns_in_range(500, 1000, 3),
+            make_patterns_in_range(500_u64, 1000, 3),
             vec![555, 666, 777, 888, 999]
         );
 
         Ok(())
     }
 
+    #[test]
+    fn test_sum_patterns_in_range() -> anyhow::Result<()> {
+        assert_eq!(sum_patterns_in_range(11_u64, 22)?, 33);
+        assert_eq!(sum_patterns_in_range(95_u64, 115)?, 99 + 111);
+        assert_eq!(sum_patterns_in_range(222220_u64, 222224)?, 222222);
+        assert_eq!(sum_patterns_in_range(1698522_u64, 1698528)?, 0);
+        assert_eq!(sum_patterns_in_range(10_u64, 1)?, 0);
+
+        let brute = |start: u64, end: u64| -> u128 {
+            (start..=end)
+                .filter(|n| {
+                    let s = n.to_string();
+                    (1..s.len()).any(|len| {
+                        s.len().is_multiple_of(len) && s.as_bytes().chunks(len).all_equal()
+                    })
+                })
+                .map(u128::from)
+                .sum()
+        };
+        for (start, end) in [
+            (0, 10_000),
+            (1, 99),
+            (100, 100_000),
+            (123, 4567),
+            (998, 1012),
+            (18446744071844674000, 18446744071844675000),
+            (u64::MAX - 1000, u64::MAX),
+        ] {
+            assert_eq!(
+                sum_patterns_in_range(u128::from(start), u128::from(end))?,
+                brute(start, end)
+            );
+            assert_eq!(
+                sum_doubles_in_range(u128::from(start), u128::from(end))?,
+                make_doubles(start, end).into_iter().map(u128::from).sum()
+            );
+        }
+        assert_eq!(
+            sum_patterns_in_range(18446744071844674000_u64, 18446744071844675000)?,
+            18446744071844674407
+        );
+
+        // make_patterns only handles ranges that span at most two digit counts, which is all
+        // the puzzle has
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 2);
+        for range in input.trim().split(',') {
+            let (start, end) = range.split_once('-').context("expected range")?;
+            let (start, end): (u64, u64) = (start.parse()?, end.parse()?);
+            let mut patterns = BTreeSet::new();
+            make_patterns(start, end, &mut patterns);
+            assert_eq!(
+                sum_patterns_in_range(start, end)?,
+                patterns.iter().sum::<u64>()
+            );
+        }
+
+        // The sum of every invalid u64 only fits in a u128
+        assert!(sum_patterns_in_range(0, u64::MAX).is_err());
+        assert!(sum_patterns_in_range(0, u128::from(u64::MAX))? > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_range() -> anyhow::Result<()> {
+        let explained: Vec<String> = explain_range(95_u64, 115)
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(explained, vec!["99 = \"9\" x 2", "111 = \"1\" x 3"]);
+
+        let explained = explain_range(121212_u64, 222222);
+        assert_eq!(
+            explained.first(),
+            Some(&Explanation {
+                id: 121212,
+                patterns: vec![(12, 3)]
+            })
+        );
+        assert_eq!(
+            explained.last().map(|e| e.to_string()),
+            Some("222222 = \"2\" x 6, \"22\" x 3, \"222\" x 2 (multiple patterns)".to_string())
+        );
+
+        // Every ID must be counted once, the same as part two
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 2);
+        let ranges = parse_ranges(input)?;
+        let total: u128 = ranges
+            .iter()
+            .flat_map(|(start, end)| explain_range(*start, *end))
+            .map(|e| e.id)
+            .sum();
+        assert_eq!(Some(total), part_two(input, RunType::Example)?);
+
+        let rendered = render_explanation(&ranges)?;
+        assert!(rendered.contains(
+            "222220-222224:\n    222222 = \"2\" x 6, \"22\" x 3, \"222\" x 2 (multiple patterns)\n"
+        ));
+        assert!(rendered.lines().last().unwrap().ends_with(" 4174379265"));
+
+        // Overlapping ranges are listed as given, with 22 flagged in both but counted once
+        let input = "11-22,15-30";
+        assert_eq!(part_two(input, RunType::Example)?, Some(33));
+        assert_eq!(merge_ranges(parse_ranges(input)?), vec![(11, 30)]);
+        let rendered = render_explanation(&parse_ranges(input)?)?;
+        assert!(rendered.starts_with(
+            "11-22:\n    11 = \"1\" x 2\n    22 = \"2\" x 2 (also in 15-30)\n\
+             15-30:\n    22 = \"2\" x 2 (also in 11-22)\n"
+        ));
+        let table: Vec<&str> = rendered.lines().rev().take(3).collect();
+        assert_eq!(
+            table,
+            vec![
+                "total    2                  33",
+                "15-30    1      0       1   22",
+                "11-22    2      0       1   33",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_wide_ranges() -> anyhow::Result<()> {
+        let start = 123456789012345_123456789012340_u128;
+        let end = 123456789012345_123456789012350_u128;
+        assert_eq!(
+            make_doubles(start, end),
+            vec![123456789012345_123456789012345_u128]
+        );
+        // Patterns past u64::MAX stop the range instead of wrapping around
+        assert_eq!(make_doubles(u64::MAX - 10, u64::MAX), Vec::<u64>::new());
+
+        let input = format!("{start}-{end},11-22");
+        assert_eq!(
+            part_one(&input, RunType::Example)?,
+            Some(123456789012345_123456789012345_u128 + 33)
+        );
+        assert_eq!(
+            part_two(&input, RunType::Example)?,
+            Some(123456789012345_123456789012345_u128 + 33)
+        );
+
+        // Totals worked out separately by summing numbers by their shortest repeating block.
+        // About 10^9 doubles in the first, which is far too many to generate.
+        let input = "123456789012345678901234567890-123457789012345678901234567890";
+        assert_eq!(
+            part_one(input, RunType::Example)?,
+            Some(123457289012345623457289012345500000000)
+        );
+        assert_eq!(
+            part_two(input, RunType::Example)?,
+            Some(123458523708692370371701431814686018457)
+        );
+        // 20 to 22 digits, so the 21 digit IDs (only made of 3 or 7 copies) count too
+        let input = "12345678901234567890-3456789012345678901234";
+        assert_eq!(
+            part_one(input, RunType::Example)?,
+            Some(55239330591196227276408292180521)
+        );
+        assert_eq!(
+            part_two(input, RunType::Example)?,
+            Some(55244281617633237022422979684893)
+        );
+
+        // Around 5 * 10^41, too big for a u128
+        assert!(part_one("1-100000000000000000000000000000", RunType::Example).is_err());
+        assert!(part_two("1-100000000000000000000000000000", RunType::Example).is_err());
+
+        let input = format!("{}-{}", u128::MAX - 10, u128::MAX);
+        assert_eq!(part_one(&input, RunType::Example)?, Some(0));
+        assert_eq!(part_two(&input, RunType::Example)?, Some(0));
+        Ok(())
+    }
+
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
         let expected = Some(1227775554);
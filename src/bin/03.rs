@@ -3,9 +3,12 @@ advent_of_code::solution!(3);
 use advent_of_code::template::RunType;
 
 use anyhow::{Context, Result};
+#[cfg(test)]
 use aoc_lib::grid::{Location, Map};
 use aoc_lib::parse::preamble::*;
 
+// Only used to cross-check select_batteries in tests
+#[cfg(test)]
 fn cached_seek(
     input: &[u64],
     position: usize,
@@ -36,7 +39,129 @@ fn cached_seek(
     best
 }
 
-fn run(input: &str, digits: usize) -> Result<Option<u64>, anyhow::Error> {
+// Decimal number of any length, for when we keep more batteries than fit in a u64.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Joltage {
+    // Least significant digit first with no trailing zeros, so 0 is empty
+    digits: Vec<u8>,
+}
+
+impl Joltage {
+    // Digits are most significant first, the same order as a bank.
+    pub fn from_digits(digits: impl IntoIterator<Item = u64>) -> Self {
+        let mut digits: Vec<u8> = digits
+            .into_iter()
+            .map(|d| u8::try_from(d).expect("expected single digit"))
+            .collect();
+        digits.reverse();
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self { digits }
+    }
+}
+
+impl From<u64> for Joltage {
+    fn from(other: u64) -> Self {
+        Self::from_digits(other.to_string().bytes().map(|b| (b - b'0').into()))
+    }
+}
+
+impl TryFrom<&Joltage> for u64 {
+    type Error = anyhow::Error;
+
+    fn try_from(other: &Joltage) -> Result<Self> {
+        other.digits.iter().rev().try_fold(0_u64, |acc, d| {
+            acc.checked_mul(10)
+                .and_then(|acc| acc.checked_add((*d).into()))
+                .with_context(|| format!("joltage {other} does not fit in a u64"))
+        })
+    }
+}
+
+impl std::ops::AddAssign<&Joltage> for Joltage {
+    fn add_assign(&mut self, other: &Joltage) {
+        let mut carry = 0;
+        for idx in 0..std::cmp::max(self.digits.len(), other.digits.len()) {
+            if idx == self.digits.len() {
+                self.digits.push(0);
+            }
+            let sum = self.digits[idx] + other.digits.get(idx).copied().unwrap_or(0) + carry;
+            self.digits[idx] = sum % 10;
+            carry = sum / 10;
+        }
+        if carry > 0 {
+            self.digits.push(carry);
+        }
+    }
+}
+
+impl std::fmt::Display for Joltage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        for d in self.digits.iter().rev() {
+            write!(f, "{d}")?;
+        }
+        Ok(())
+    }
+}
+
+// Set to any value to print every bank with the selected batteries highlighted.
+const SHOW_ENV: &str = "AOC_DAY3_SHOW";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selection {
+    // Positions in the bank of the batteries we turned on, always increasing
+    pub indices: Vec<usize>,
+    pub joltage: Joltage,
+}
+
+// Largest number we can make by keeping `digits` of the batteries in order. Any digit that is
+// smaller than a later one gets dropped while we still have spare batteries to drop, so the
+// stack only ever holds the best prefix we have found so far.
+fn select_batteries(input: &[u64], digits: usize) -> Selection {
+    if digits == 0 || input.len() < digits {
+        return Selection::default();
+    }
+
+    let mut stack: Vec<usize> = Vec::with_capacity(digits);
+    for (idx, value) in input.iter().enumerate() {
+        let remaining = input.len() - idx;
+        while let Some(top) = stack.last()
+            && input[*top] < *value
+            && stack.len() - 1 + remaining >= digits
+        {
+            stack.pop();
+        }
+        if stack.len() < digits {
+            stack.push(idx);
+        }
+    }
+
+    Selection {
+        joltage: Joltage::from_digits(stack.iter().map(|idx| input[*idx])),
+        indices: stack,
+    }
+}
+
+// The bank with the selected batteries in bold green.
+pub fn render_selection(input: &[u64], selection: &Selection) -> String {
+    let mut selected = selection.indices.iter().peekable();
+    let mut out = String::new();
+    for (idx, value) in input.iter().enumerate() {
+        if selected.next_if_eq(&&idx).is_some() {
+            out.push_str(&format!("\x1b[1;32m{value}\x1b[0m"));
+        } else {
+            out.push_str(&value.to_string());
+        }
+    }
+    out
+}
+
+// Sum of the best joltage from every bank when keeping `digits` batteries, works for any size.
+pub fn total_joltage(input: &str, digits: usize) -> Result<Joltage> {
     let data: Vec<Vec<u64>> = parse_input(
         LineSplitter,
         Chars(ParseFn(|c: char| {
@@ -46,14 +171,26 @@ fn run(input: &str, digits: usize) -> Result<Option<u64>, anyhow::Error> {
     )
     .context("failed to parse input")?;
 
-    let mut out = 0;
+    let show = std::env::var_os(SHOW_ENV).is_some();
+    let mut out = Joltage::default();
     for row in data {
-        let mut cache = Map::from_dimensions(row.len(), digits + 1, |_| None::<u64>);
-        let best = cached_seek(&row, 0, digits, &mut cache);
-        out += best;
+        let selection = select_batteries(&row, digits);
+        if show {
+            println!(
+                "{} -> {}",
+                render_selection(&row, &selection),
+                selection.joltage
+            );
+        }
+        out += &selection.joltage;
     }
 
-    Ok(Some(out))
+    Ok(out)
+}
+
+fn run(input: &str, digits: usize) -> Result<Option<u64>, anyhow::Error> {
+    let total = total_joltage(input, digits)?;
+    Ok(Some(u64::try_from(&total)?))
 }
 
 pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
@@ -64,9 +201,141 @@ pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::
     run(input, 12)
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_3 {
     use super::*;
+    use crate::test_rng::Rng;
+
+    fn seek(row: &[u64], digits: usize) -> u64 {
+        let mut cache = Map::from_dimensions(row.len(), digits + 1, |_| None::<u64>);
+        cached_seek(row, 0, digits, &mut cache)
+    }
+
+    #[test]
+    fn test_select_batteries_matches_cached_seek() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 1);
+        let mut rows: Vec<Vec<u64>> = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(|c| c.to_digit(10).unwrap().into())
+                    .collect()
+            })
+            .collect();
+
+        // Plus some pseudo-random rows with plenty of repeated digits
+        let mut rng = Rng::new(0x2025_0003);
+        for len in 0..40 {
+            let row = (0..len).map(|_| rng.below(10)).collect();
+            rows.push(row);
+        }
+
+        for row in rows {
+            for digits in 0..=12 {
+                assert_eq!(
+                    select_batteries(&row, digits).joltage,
+                    Joltage::from(seek(&row, digits)),
+                    "row={row:?} digits={digits}"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_joltage() -> anyhow::Result<()> {
+        let mut total = Joltage::from(999);
+        total += &Joltage::from(1);
+        assert_eq!(total.to_string(), "1000");
+        assert_eq!(Joltage::default().to_string(), "0");
+        assert_eq!(Joltage::from_digits([0, 0, 4, 2]).to_string(), "42");
+        assert_eq!(u64::try_from(&Joltage::from(u64::MAX))?, u64::MAX);
+
+        total = Joltage::from(u64::MAX);
+        total += &Joltage::from(1);
+        assert_eq!(total.to_string(), "18446744073709551616");
+        assert!(u64::try_from(&total).is_err());
+        Ok(())
+    }
+
+    // Picks the first biggest digit that still leaves enough batteries after it, O(n * k)
+    fn window_greedy(row: &[u64], digits: usize) -> String {
+        let mut out = String::new();
+        let mut start = 0;
+        for remaining in (1..=digits).rev() {
+            let window = &row[start..=row.len() - remaining];
+            let best = *window.iter().max().unwrap();
+            start += window.iter().position(|d| *d == best).unwrap() + 1;
+            out.push_str(&best.to_string());
+        }
+        out
+    }
+
+    #[test]
+    fn test_total_joltage_many_digits() -> anyhow::Result<()> {
+        let rows = [
+            "98765432111111111111111111111111111111111111111111111111111119",
+            "12345678912345678912345678912345678912345678912345678912345678",
+        ];
+        for row in rows {
+            let row: Vec<u64> = row
+                .chars()
+                .map(|c| c.to_digit(10).unwrap().into())
+                .collect();
+            for digits in [20, 30, 50] {
+                assert_eq!(
+                    select_batteries(&row, digits).joltage.to_string(),
+                    window_greedy(&row, digits)
+                );
+            }
+        }
+
+        let input = rows.join("\n");
+        assert_eq!(
+            total_joltage(&input, 50)?.to_string(),
+            "194444344456790023456790023456790023456790023456797"
+        );
+
+        // Banks shorter than k can't be turned on
+        assert_eq!(total_joltage("123\n45", 3)?.to_string(), "123");
+
+        let example = &advent_of_code::template::read_file_part("examples", DAY, 1);
+        assert_eq!(total_joltage(example, 12)?.to_string(), "3121910778619");
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_batteries() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 1);
+        for line in input.lines() {
+            let row: Vec<u64> = line
+                .chars()
+                .map(|c| c.to_digit(10).unwrap().into())
+                .collect();
+            for digits in [1, 2, 5, 12, 15] {
+                let selection = select_batteries(&row, digits);
+                assert_eq!(selection.indices.len(), digits);
+                assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(
+                    Joltage::from_digits(selection.indices.iter().map(|idx| row[*idx])),
+                    selection.joltage
+                );
+            }
+        }
+
+        let selection = select_batteries(&[8, 1, 1, 9, 1, 2], 2);
+        assert_eq!(selection.indices, vec![3, 5]);
+        assert_eq!(selection.joltage, Joltage::from(92));
+        assert_eq!(
+            render_selection(&[8, 1, 1, 9, 1, 2], &selection),
+            "811\x1b[1;32m9\x1b[0m1\x1b[1;32m2\x1b[0m"
+        );
+        Ok(())
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
@@ -2,7 +2,7 @@ advent_of_code::solution!(4);
 
 use advent_of_code::template::RunType;
 
-use aoc_lib::grid::{Direction, Location, Map};
+use aoc_lib::grid::{Location, Map};
 
 use anyhow::{Context, Result, anyhow};
 
@@ -26,87 +26,607 @@ impl TryFrom<char> for Tile {
     }
 }
 
-fn get_left_right(map: &Map<Tile>, loc: Option<Location>, mid: bool) -> usize {
-    let mut count = 0;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    // The 4 orthogonal neighbours
+    VonNeumann,
+    // All 8 surrounding cells
+    Moore,
+    // (row, col) offsets from the cell
+    Custom(Vec<(isize, isize)>),
+}
 
-    let loc = match loc {
-        Some(loc) => loc,
-        None => return 0,
-    };
+impl Neighbourhood {
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Self::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Self::Moore => vec![
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, -1),
+                (0, 1),
+                (1, -1),
+                (1, 0),
+                (1, 1),
+            ],
+            Self::Custom(offsets) => offsets.clone(),
+        }
+    }
+}
+
+fn neighbour_locations<'a, T>(
+    map: &'a Map<T>,
+    loc: &'a Location,
+    offsets: &'a [(isize, isize)],
+) -> impl Iterator<Item = Location> + 'a {
+    offsets.iter().filter_map(|(d_row, d_col)| {
+        let row = loc.0.checked_add_signed(*d_row)?;
+        let col = loc.1.checked_add_signed(*d_col)?;
+        map.0.get(row)?.get(col)?;
+        Some(Location(row, col))
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateMode {
+    // Every cell sees the map as it was at the start of the step
+    Synchronous,
+    // Cells are updated in reading order and later cells see earlier changes straight away
+    Asynchronous,
+}
 
-    if mid && map.get(&loc) == &Tile::Paper {
-        count += 1;
+// Cellular automaton over a Map where the rule gets a cell and its neighbours and returns the
+// new value for the cell, or None to leave it alone.
+pub struct Automaton<F> {
+    offsets: Vec<(isize, isize)>,
+    mode: UpdateMode,
+    rule: F,
+}
+
+impl<F> Automaton<F> {
+    pub fn new(neighbourhood: Neighbourhood, mode: UpdateMode, rule: F) -> Self {
+        Self {
+            offsets: neighbourhood.offsets(),
+            mode,
+            rule,
+        }
+    }
+
+    fn neighbours<'a, T>(&self, map: &'a Map<T>, loc: &Location) -> Vec<&'a T> {
+        neighbour_locations(map, loc, &self.offsets)
+            .map(|loc| map.get(&loc))
+            .collect()
     }
-    if let Some(upleft) = map.go_direction(&loc, &Direction::West)
-        && map.get(&upleft) == &Tile::Paper
+
+    // Applies the rule to every cell once, returning how many cells changed.
+    pub fn step<T>(&self, map: &mut Map<T>) -> usize
+    where
+        F: Fn(&T, &[&T]) -> Option<T>,
     {
-        count += 1;
+        let locations: Vec<Location> = map
+            .0
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| (0..cells.len()).map(move |col| Location(row, col)))
+            .collect();
+
+        match self.mode {
+            UpdateMode::Synchronous => {
+                let changes: Vec<(Location, T)> = locations
+                    .into_iter()
+                    .filter_map(|loc| {
+                        let value = (self.rule)(map.get(&loc), &self.neighbours(map, &loc))?;
+                        Some((loc, value))
+                    })
+                    .collect();
+                let changed = changes.len();
+                for (loc, value) in changes {
+                    *map.get_mut(&loc) = value;
+                }
+                changed
+            }
+            UpdateMode::Asynchronous => {
+                let mut changed = 0;
+                for loc in locations {
+                    if let Some(value) = (self.rule)(map.get(&loc), &self.neighbours(map, &loc)) {
+                        *map.get_mut(&loc) = value;
+                        changed += 1;
+                    }
+                }
+                changed
+            }
+        }
     }
-    if let Some(upright) = map.go_direction(&loc, &Direction::East)
-        && map.get(&upright) == &Tile::Paper
+
+    // Steps until nothing changes, returning how many cells changed in each step.
+    pub fn run<T>(&self, map: &mut Map<T>) -> Vec<usize>
+    where
+        F: Fn(&T, &[&T]) -> Option<T>,
     {
-        count += 1;
+        let mut out = Vec::new();
+        loop {
+            let changed = self.step(map);
+            if changed == 0 {
+                return out;
+            }
+            out.push(changed);
+        }
     }
-    count
 }
-pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
-    let map: Map<Tile> = input.try_into().context("failed to parse map")?;
 
-    let mut out = 0;
-    for row in map.iter() {
-        for (loc, value) in row {
-            if value == &Tile::Empty {
-                continue;
-            }
+// Set to override how many paper neighbours make a roll inaccessible, 4 by default.
+const THRESHOLD_ENV: &str = "AOC_DAY4_THRESHOLD";
+// Set to "moore" (the default), "von-neumann" or custom offsets like "-1,0;1,0".
+const NEIGHBOURHOOD_ENV: &str = "AOC_DAY4_NEIGHBOURHOOD";
+
+impl std::str::FromStr for Neighbourhood {
+    type Err = anyhow::Error;
 
-            let mut count = 0;
-            count += get_left_right(&map, Some(loc.clone()), false);
-            count += get_left_right(&map, map.go_direction(&loc, &Direction::North), true);
-            count += get_left_right(&map, map.go_direction(&loc, &Direction::South), true);
-            if count < 4 {
-                out += 1;
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "moore" => Ok(Self::Moore),
+            "von-neumann" => Ok(Self::VonNeumann),
+            custom => {
+                let mut offsets = Vec::new();
+                for offset in custom.split(';') {
+                    let (row, col) = offset
+                        .split_once(',')
+                        .with_context(|| format!("expected row,col offset but got {offset:?}"))?;
+                    offsets.push((
+                        row.trim().parse().context("failed to parse row offset")?,
+                        col.trim().parse().context("failed to parse col offset")?,
+                    ));
+                }
+                Ok(Self::Custom(offsets))
             }
         }
     }
+}
 
-    Ok(Some(out))
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    // Paper with fewer than this many paper neighbours is accessible
+    pub threshold: usize,
+    pub neighbourhood: Neighbourhood,
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            threshold: 4,
+            neighbourhood: Neighbourhood::Moore,
+        }
+    }
+}
+
+impl Config {
+    pub fn from_env() -> Result<Self> {
+        let mut config = Self::default();
+        if let Ok(threshold) = std::env::var(THRESHOLD_ENV) {
+            config.threshold = threshold
+                .parse()
+                .with_context(|| format!("invalid {THRESHOLD_ENV}"))?;
+        }
+        if let Ok(neighbourhood) = std::env::var(NEIGHBOURHOOD_ENV) {
+            config.neighbourhood = neighbourhood
+                .parse()
+                .with_context(|| format!("invalid {NEIGHBOURHOOD_ENV}"))?;
+        }
+        Ok(config)
+    }
+}
+
+// Paper with fewer than `threshold` paper neighbours can be reached by a forklift and removed
+fn remove_accessible(threshold: usize) -> impl Fn(&Tile, &[&Tile]) -> Option<Tile> {
+    move |tile, neighbours| {
+        let paper = neighbours.iter().filter(|n| ***n == Tile::Paper).count();
+        if tile == &Tile::Paper && paper < threshold {
+            Some(Tile::Empty)
+        } else {
+            None
+        }
+    }
+}
+
+fn count_accessible(map: &mut Map<Tile>, config: &Config) -> usize {
+    let automaton = Automaton::new(
+        config.neighbourhood.clone(),
+        UpdateMode::Synchronous,
+        remove_accessible(config.threshold),
+    );
+    automaton.step(map)
+}
+
+pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
     let mut map: Map<Tile> = input.try_into().context("failed to parse map")?;
+    let config = Config::from_env()?;
+    Ok(Some(count_accessible(&mut map, &config) as u32))
+}
+
+// Removes paper until none is accessible, returning what was removed in each wave. Rather than
+// rescanning the whole map after every pass we count neighbours once and then only revisit the
+// neighbours of paper we removed, since nothing else can have changed. Working through one wave
+// at a time gives the same waves as removing everything accessible at once and rescanning.
+fn remove_all_accessible(map: &mut Map<Tile>, config: &Config) -> Vec<Vec<Location>> {
+    let offsets = config.neighbourhood.offsets();
+    // Custom neighbourhoods don't have to be symmetric, the cells that count a removed cell as a
+    // neighbour are the ones in the opposite direction.
+    let reverse_offsets: Vec<(isize, isize)> = offsets.iter().map(|(r, c)| (-r, -c)).collect();
+
+    let mut counts: Vec<Vec<usize>> = map.0.iter().map(|row| vec![0; row.len()]).collect();
+    let mut queued: Vec<Vec<bool>> = map.0.iter().map(|row| vec![false; row.len()]).collect();
+    let mut to_remove = Vec::new();
+    for (row, cells) in map.0.iter().enumerate() {
+        for col in 0..cells.len() {
+            let loc = Location(row, col);
+            if map.get(&loc) != &Tile::Paper {
+                continue;
+            }
+            let count = neighbour_locations(map, &loc, &offsets)
+                .filter(|n| map.get(n) == &Tile::Paper)
+                .count();
+            counts[row][col] = count;
+            if count < config.threshold {
+                queued[row][col] = true;
+                to_remove.push(loc);
+            }
+        }
+    }
+
+    let mut waves = Vec::new();
+    while !to_remove.is_empty() {
+        let mut next = Vec::new();
+        for loc in to_remove.iter() {
+            *map.get_mut(loc) = Tile::Empty;
 
-    let mut out = 0;
-    let mut removed = true;
-    while removed {
-        let mut to_remove = Vec::new();
-        for row in map.iter() {
-            for (loc, value) in row {
-                if value == &Tile::Empty {
+            let neighbours: Vec<Location> =
+                neighbour_locations(map, loc, &reverse_offsets).collect();
+            for n in neighbours {
+                if map.get(&n) != &Tile::Paper {
                     continue;
                 }
-
-                let mut count = 0;
-                count += get_left_right(&map, Some(loc.clone()), false);
-                count += get_left_right(&map, map.go_direction(&loc, &Direction::North), true);
-                count += get_left_right(&map, map.go_direction(&loc, &Direction::South), true);
-                if count < 4 {
-                    out += 1;
-                    to_remove.push(loc.clone());
+                counts[n.0][n.1] -= 1;
+                if counts[n.0][n.1] < config.threshold && !queued[n.0][n.1] {
+                    queued[n.0][n.1] = true;
+                    next.push(n);
                 }
             }
         }
+        waves.push(std::mem::replace(&mut to_remove, next));
+    }
+    waves
+}
+
+// Set to a directory to write a frame per removal wave into it.
+const EXPORT_ENV: &str = "AOC_DAY4_EXPORT";
+// Either "ascii" (the default) or "ppm".
+const EXPORT_FORMAT_ENV: &str = "AOC_DAY4_EXPORT_FORMAT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Ascii,
+    Ppm,
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        match input {
+            "ascii" => Ok(Self::Ascii),
+            "ppm" => Ok(Self::Ppm),
+            other => Err(anyhow!(
+                "unknown export format {other:?}, expected ascii or ppm"
+            )),
+        }
+    }
+}
+
+// Which wave removed each cell, None for cells that never had paper or still do.
+fn wave_map(map: &Map<Tile>, waves: &[Vec<Location>]) -> Vec<Vec<Option<usize>>> {
+    let mut out: Vec<Vec<Option<usize>>> = map.0.iter().map(|row| vec![None; row.len()]).collect();
+    for (wave, removed) in waves.iter().enumerate() {
+        for loc in removed {
+            out[loc.0][loc.1] = Some(wave);
+        }
+    }
+    out
+}
+
+// Red for the first wave fading to blue for the last.
+fn wave_colour(wave: usize, total: usize) -> [u8; 3] {
+    let t = if total > 1 {
+        wave as f64 / (total - 1) as f64
+    } else {
+        0.0
+    };
+    [(255.0 * (1.0 - t)) as u8, 64, (255.0 * t) as u8]
+}
+
+// One frame per wave showing the map just after that wave. In ASCII the paper removed by the
+// wave is 'x', in PPM removed paper is coloured by the wave that removed it. `map` is the map
+// after removal and `waves` what remove_all_accessible returned.
+fn export_waves(
+    map: &Map<Tile>,
+    waves: &[Vec<Location>],
+    dir: &std::path::Path,
+    format: ExportFormat,
+) -> Result<()> {
+    use std::fmt::Write;
+
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {dir:?}"))?;
+    let removed_in = wave_map(map, waves);
+
+    for frame in 0..waves.len() {
+        let contents: Vec<u8> = match format {
+            ExportFormat::Ascii => {
+                let mut out = String::new();
+                for (row, cells) in map.0.iter().enumerate() {
+                    for (col, tile) in cells.iter().enumerate() {
+                        out.push(match (tile, removed_in[row][col]) {
+                            (Tile::Paper, _) => '@',
+                            (_, Some(wave)) if wave == frame => 'x',
+                            (_, Some(wave)) if wave > frame => '@',
+                            _ => '.',
+                        });
+                    }
+                    out.push('\n');
+                }
+                out.into_bytes()
+            }
+            ExportFormat::Ppm => {
+                let width = map.0.first().map(|row| row.len()).unwrap_or(0);
+                let mut header = String::new();
+                write!(header, "P6\n{} {}\n255\n", width, map.0.len())?;
+                let mut out = header.into_bytes();
+                for (row, cells) in map.0.iter().enumerate() {
+                    for (col, tile) in cells.iter().enumerate() {
+                        out.extend(match (tile, removed_in[row][col]) {
+                            (Tile::Paper, _) => [255, 255, 255],
+                            (_, Some(wave)) if wave <= frame => wave_colour(wave, waves.len()),
+                            (_, Some(_)) => [255, 255, 255],
+                            _ => [0, 0, 0],
+                        });
+                    }
+                }
+                out
+            }
+        };
+
+        let extension = match format {
+            ExportFormat::Ascii => "txt",
+            ExportFormat::Ppm => "ppm",
+        };
+        let path = dir.join(format!("wave_{:04}.{extension}", frame + 1));
+        std::fs::write(&path, contents).with_context(|| format!("failed to write {path:?}"))?;
+    }
+    Ok(())
+}
+
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
+    let mut map: Map<Tile> = input.try_into().context("failed to parse map")?;
+    let config = Config::from_env()?;
+    let waves = remove_all_accessible(&mut map, &config);
+
+    if let Some(dir) = std::env::var_os(EXPORT_ENV) {
+        let format = match std::env::var(EXPORT_FORMAT_ENV) {
+            Ok(format) => format.parse()?,
+            Err(_) => ExportFormat::Ascii,
+        };
+        export_waves(&map, &waves, dir.as_ref(), format).context("failed to export waves")?;
 
-        removed = !to_remove.is_empty();
-        for loc in to_remove {
-            *map.get_mut(&loc) = Tile::Empty;
+        println!("{} waves", waves.len());
+        for (wave, removed) in waves.iter().enumerate() {
+            println!("wave {}: removed {}", wave + 1, removed.len());
         }
     }
-    Ok(Some(out))
+
+    Ok(Some(waves.iter().map(|w| w.len()).sum::<usize>() as u32))
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_4 {
     use super::*;
+    use crate::test_rng::Rng;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_automaton_modes() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 2);
+        for mode in [UpdateMode::Synchronous, UpdateMode::Asynchronous] {
+            let mut map: Map<Tile> = input.as_str().try_into()?;
+            let automaton = Automaton::new(Neighbourhood::Moore, mode, remove_accessible(4));
+            assert_eq!(
+                automaton.run(&mut map).iter().sum::<usize>(),
+                43,
+                "{mode:?}"
+            );
+        }
+
+        // A blinker from game of life only works if every cell sees the same generation
+        let life = |alive: &bool, neighbours: &[&bool]| {
+            let count = neighbours.iter().filter(|n| ***n).count();
+            let next = count == 3 || (*alive && count == 2);
+            if next != *alive { Some(next) } else { None }
+        };
+        let automaton = Automaton::new(Neighbourhood::Moore, UpdateMode::Synchronous, life);
+        let mut map = Map::from_dimensions(5, 5, |_| false);
+        for col in 1..=3 {
+            *map.get_mut(&Location(2, col)) = true;
+        }
+        assert_eq!(automaton.step(&mut map), 4);
+        assert_eq!(map.find(|(_, alive)| **alive), Some(Location(1, 2)));
+        assert!(*map.get(&Location(3, 2)) && !*map.get(&Location(2, 1)));
+
+        let count = |neighbourhood: Neighbourhood| {
+            let automaton = Automaton::new(
+                neighbourhood,
+                UpdateMode::Synchronous,
+                |_: &bool, _: &[&bool]| None::<bool>,
+            );
+            let map = Map::from_dimensions(3, 3, |_| true);
+            (
+                automaton.neighbours(&map, &Location(1, 1)).len(),
+                automaton.neighbours(&map, &Location(0, 0)).len(),
+            )
+        };
+        assert_eq!(count(Neighbourhood::Moore), (8, 3));
+        assert_eq!(count(Neighbourhood::VonNeumann), (4, 2));
+        assert_eq!(count(Neighbourhood::Custom(vec![(0, 2), (2, 2)])), (0, 2));
+        Ok(())
+    }
+
+    fn generate_map(size: usize, seed: u64) -> Map<Tile> {
+        let mut rng = Rng::new(seed);
+        let mut map = Map::from_dimensions(size, size, |_| Tile::Empty);
+        for row in 0..size {
+            for col in 0..size {
+                // Roughly 70% paper gives plenty of waves before things settle
+                if rng.below(10) < 7 {
+                    *map.get_mut(&Location(row, col)) = Tile::Paper;
+                }
+            }
+        }
+        map
+    }
+
+    fn rescan_all(map: &mut Map<Tile>, config: &Config) -> Vec<usize> {
+        let automaton = Automaton::new(
+            config.neighbourhood.clone(),
+            UpdateMode::Synchronous,
+            remove_accessible(config.threshold),
+        );
+        automaton.run(map)
+    }
+
+    #[test]
+    fn test_remove_all_accessible() -> anyhow::Result<()> {
+        let configs = [
+            Config::default(),
+            Config {
+                threshold: 3,
+                neighbourhood: Neighbourhood::VonNeumann,
+            },
+            Config {
+                threshold: 2,
+                neighbourhood: "0,1;1,1;2,0".parse()?,
+            },
+        ];
+        for (seed, config) in (0..20).cartesian_product(configs.iter()) {
+            let mut a = generate_map(30, seed);
+            let mut b = generate_map(30, seed);
+            let waves = remove_all_accessible(&mut a, config);
+            assert_eq!(
+                waves.iter().map(|w| w.len()).collect::<Vec<_>>(),
+                rescan_all(&mut b, config)
+            );
+            assert_eq!(a.0, b.0);
+        }
+        Ok(())
+    }
+
+    // cargo test --release --bin 04 -- --ignored --nocapture
+    // AOC_DAY4_BENCH_SIZE can be used to try other sizes.
+    #[test]
+    #[ignore]
+    fn bench_remove_all_accessible() {
+        let size = std::env::var("AOC_DAY4_BENCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(5000);
+
+        let mut a = generate_map(size, 2025);
+        let mut b = generate_map(size, 2025);
+
+        let start = std::time::Instant::now();
+        let worklist: usize = remove_all_accessible(&mut a, &Config::default())
+            .iter()
+            .map(|w| w.len())
+            .sum();
+        let worklist_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let rescan: usize = rescan_all(&mut b, &Config::default()).iter().sum();
+        let rescan_time = start.elapsed();
+
+        assert_eq!(worklist, rescan);
+        println!(
+            "{size}x{size}: removed {worklist}, worklist {worklist_time:?}, full rescans {rescan_time:?} ({:.1}x)",
+            rescan_time.as_secs_f64() / worklist_time.as_secs_f64()
+        );
+    }
+
+    #[test]
+    fn test_export_waves() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 2);
+        let mut map: Map<Tile> = input.as_str().try_into()?;
+        let waves = remove_all_accessible(&mut map, &Config::default());
+        assert_eq!(
+            waves.iter().map(|w| w.len()).collect::<Vec<_>>(),
+            vec![13, 12, 7, 5, 2, 1, 1, 1, 1]
+        );
+
+        let dir = std::env::temp_dir().join(format!("aoc_day4_export_{}", std::process::id()));
+        export_waves(&map, &waves, &dir, ExportFormat::Ascii)?;
+        export_waves(&map, &waves, &dir, ExportFormat::Ppm)?;
+
+        let first = std::fs::read_to_string(dir.join("wave_0001.txt"))?;
+        assert_eq!(first.matches('x').count(), 13);
+        assert_eq!(first.lines().next(), Some("..xx.xx@x."));
+        let last = std::fs::read_to_string(dir.join("wave_0009.txt"))?;
+        assert_eq!(last.matches('x').count(), 1);
+        assert_eq!(last.matches('@').count(), 71 - 43);
+
+        let ppm = std::fs::read(dir.join("wave_0009.ppm"))?;
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(ppm.len(), "P6\n10 10\n255\n".len() + 10 * 10 * 3);
+        assert!(!dir.join("wave_0010.ppm").exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_configs() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 1);
+        let run = |config: &Config| -> anyhow::Result<(usize, usize)> {
+            let mut map: Map<Tile> = input.as_str().try_into()?;
+            let accessible = count_accessible(&mut map, config);
+            let mut map: Map<Tile> = input.as_str().try_into()?;
+            let removed = remove_all_accessible(&mut map, config);
+            Ok((accessible, removed.iter().map(|w| w.len()).sum()))
+        };
+
+        assert_eq!(run(&Config::default())?, (13, 43));
+        for (threshold, neighbourhood, expected) in [
+            (3, Neighbourhood::Moore, (4, 4)),
+            (5, Neighbourhood::Moore, (30, 71)),
+            (2, Neighbourhood::VonNeumann, (11, 16)),
+            (3, Neighbourhood::VonNeumann, (37, 71)),
+        ] {
+            let config = Config {
+                threshold,
+                neighbourhood,
+            };
+            assert_eq!(run(&config)?, expected, "{config:?}");
+        }
+
+        assert_eq!(
+            "von-neumann".parse::<Neighbourhood>()?,
+            Neighbourhood::VonNeumann
+        );
+        assert_eq!(
+            "-1,0; 1,0".parse::<Neighbourhood>()?,
+            Neighbourhood::Custom(vec![(-1, 0), (1, 0)])
+        );
+        assert!("sideways".parse::<Neighbourhood>().is_err());
+        Ok(())
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
@@ -2,73 +2,517 @@ advent_of_code::solution!(5);
 
 use advent_of_code::template::RunType;
 use anyhow::{Context, Result};
-use aoc_lib::parse::preamble::*;
 
-pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
-    let (sec1, sec2) = input.split_once("\n\n").context("failed to split input")?;
+// Set of u64s stored as sorted, inclusive ranges. Ranges are merged as they are inserted so
+// there are never two that overlap or touch.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeSet {
+    ranges: Vec<(u64, u64)>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // An empty range (start > end) is ignored.
+    pub fn insert(&mut self, start: u64, end: u64) {
+        if start > end {
+            return;
+        }
+        // Everything in first..last overlaps or touches the new range
+        let first = self
+            .ranges
+            .partition_point(|(_, e)| start > 0 && *e < start - 1);
+        let last = self
+            .ranges
+            .partition_point(|(s, _)| end.checked_add(1).is_none_or(|after| *s <= after));
+
+        let mut merged = (start, end);
+        if first < last {
+            merged.0 = std::cmp::min(merged.0, self.ranges[first].0);
+            merged.1 = std::cmp::max(merged.1, self.ranges[last - 1].1);
+        }
+        self.ranges.splice(first..last, [merged]);
+    }
+
+    pub fn contains(&self, value: u64) -> bool {
+        let idx = self.ranges.partition_point(|(_, e)| *e < value);
+        self.ranges.get(idx).is_some_and(|(s, _)| *s <= value)
+    }
+
+    // Number of values in the set, a u128 since 0..=u64::MAX doesn't fit in a u64.
+    pub fn len(&self) -> u128 {
+        self.ranges.iter().map(|(s, e)| u128::from(e - s) + 1).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.ranges.iter().copied()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut out = self.clone();
+        for (start, end) in other.iter() {
+            out.insert(start, end);
+        }
+        out
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut out = Self::new();
+        let (mut a, mut b) = (
+            self.ranges.iter().peekable(),
+            other.ranges.iter().peekable(),
+        );
+        while let (Some((a_start, a_end)), Some((b_start, b_end))) = (a.peek(), b.peek()) {
+            let start = std::cmp::max(a_start, b_start);
+            let end = std::cmp::min(a_end, b_end);
+            if start <= end {
+                out.ranges.push((*start, *end));
+            }
+            // Whichever finishes first can't overlap anything else
+            if a_end < b_end {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        out
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement(0, u64::MAX))
+    }
 
-    let mut ranges: Vec<(u64, u64)> = parse_input(
-        LineSplitter,
-        ParseTuple2(ParseFromStr, ParseFromStr, "-"),
-        sec1,
-    )
-    .context("failed to parse input")?;
+    // Walks the sorted ids and the ranges together, yielding the range each id is in. Each step
+    // moves forward through one of the two so it's a single pass over both.
+    fn sweep<'a>(&'a self, sorted_ids: &'a [u64]) -> impl Iterator<Item = Option<(u64, u64)>> + 'a {
+        let mut ranges = self.iter().peekable();
+        sorted_ids.iter().map(move |id| {
+            while ranges.next_if(|(_, end)| end < id).is_some() {}
+            ranges.peek().copied().filter(|(start, _)| start <= id)
+        })
+    }
 
-    let ids: Vec<u64> =
-        parse_input(LineSplitter, ParseFromStr, sec2).context("failed to parse input")?;
+    // Number of ids that are in the set. The ids are sorted in place first, which is close to
+    // free if they already are.
+    pub fn count_fresh(&self, ids: &mut [u64]) -> usize {
+        ids.sort_unstable();
+        self.sweep(ids).filter(Option::is_some).count()
+    }
 
-    ranges.sort();
+    // The range each id is in, in the same order as `ids`.
+    pub fn match_ids(&self, ids: &[u64]) -> Vec<Option<(u64, u64)>> {
+        let mut order: Vec<usize> = (0..ids.len()).collect();
+        order.sort_unstable_by_key(|idx| ids[*idx]);
+        let sorted: Vec<u64> = order.iter().map(|idx| ids[*idx]).collect();
 
-    let mut out = 0;
-    for id in ids {
-        for (start, end) in &ranges {
-            if id >= *start && id <= *end {
-                out += 1;
+        let mut out = vec![None; ids.len()];
+        for (idx, matched) in order.into_iter().zip(self.sweep(&sorted)) {
+            out[idx] = matched;
+        }
+        out
+    }
+
+    // Everything in lo..=hi that is not in the set.
+    pub fn complement(&self, lo: u64, hi: u64) -> Self {
+        let mut out = Self::new();
+        if lo > hi {
+            return out;
+        }
+        let mut next = Some(lo);
+        for (start, end) in self.iter() {
+            let Some(from) = next else {
                 break;
+            };
+            if start > hi {
+                break;
+            }
+            if end < from {
+                continue;
             }
+            if start > from {
+                out.ranges.push((from, start - 1));
+            }
+            next = end.checked_add(1);
+        }
+        if let Some(from) = next
+            && from <= hi
+        {
+            out.ranges.push((from, hi));
         }
+        out
     }
+}
 
-    Ok(Some(out))
+impl FromIterator<(u64, u64)> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = (u64, u64)>>(iter: I) -> Self {
+        let mut out = Self::new();
+        for (start, end) in iter {
+            out.insert(start, end);
+        }
+        out
+    }
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
-    let (sec1, _) = input.split_once("\n\n").context("failed to split input")?;
-
-    let mut ranges: Vec<(u64, u64)> = parse_input(
-        LineSplitter,
-        ParseTuple2(ParseFromStr, ParseFromStr, "-"),
-        sec1,
-    )
-    .context("failed to parse input")?;
-    ranges.sort();
-
-    let mut out = 0;
-    let mut current = None;
-    for (start, end) in ranges {
-        if let Some((current_start, current_end)) = &mut current {
-            if start <= *current_end {
-                *current_end = std::cmp::max(end, *current_end);
+// Set to any value to swap reversed ranges like `20-10` round and skip duplicate ranges instead of
+// rejecting them.
+const LENIENT_ENV: &str = "AOC_DAY5_LENIENT";
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum InputError {
+    #[error("line {line}: expected a range like \"3-5\" but found {text:?}")]
+    Range { line: usize, text: String },
+    #[error("line {line}: range {start}-{end} is reversed")]
+    Reversed { line: usize, start: u64, end: u64 },
+    #[error("line {line}: range {start}-{end} is a duplicate of line {first_line}")]
+    Duplicate {
+        line: usize,
+        first_line: usize,
+        start: u64,
+        end: u64,
+    },
+    #[error("line {line}: expected a blank line between the ranges and the ids")]
+    MissingSeparator { line: usize },
+    #[error("line {line}: expected an ingredient id but found {text:?}")]
+    Id { line: usize, text: String },
+}
+
+// Ranges, then a blank line, then one id per line. Line numbers are 1-based and count from the
+// top of the whole input. In lenient mode a reversed range is swapped round and an exact duplicate
+// is skipped, each with a warning.
+pub fn parse_database(input: &str, lenient: bool) -> Result<(RangeSet, Vec<u64>), InputError> {
+    let mut lines = input
+        .lines()
+        .enumerate()
+        .map(|(idx, l)| (idx + 1, l.trim()));
+
+    let mut ranges = RangeSet::new();
+    let mut seen: std::collections::HashMap<(u64, u64), usize> = std::collections::HashMap::new();
+    let mut last_line = 0;
+    loop {
+        let Some((line, text)) = lines.next() else {
+            return Err(InputError::MissingSeparator {
+                line: last_line + 1,
+            });
+        };
+        last_line = line;
+        if text.is_empty() {
+            break;
+        }
+        let Some((start, end)) = text.split_once('-') else {
+            // A bare number means the ids started without the blank line
+            return Err(if text.parse::<u64>().is_ok() {
+                InputError::MissingSeparator { line }
             } else {
-                let range_size = *current_end - *current_start + 1;
-                out += range_size;
-                current = Some((start, end));
+                InputError::Range {
+                    line,
+                    text: text.to_string(),
+                }
+            });
+        };
+        let (Ok(mut start), Ok(mut end)) = (start.parse::<u64>(), end.parse::<u64>()) else {
+            return Err(InputError::Range {
+                line,
+                text: text.to_string(),
+            });
+        };
+
+        if start > end {
+            if !lenient {
+                return Err(InputError::Reversed { line, start, end });
             }
-        } else {
-            current = Some((start, end));
+            eprintln!(
+                "warning: line {line}: reversed range {start}-{end} treated as {end}-{start}"
+            );
+            std::mem::swap(&mut start, &mut end);
         }
+        if let Some(&first_line) = seen.get(&(start, end)) {
+            if !lenient {
+                return Err(InputError::Duplicate {
+                    line,
+                    first_line,
+                    start,
+                    end,
+                });
+            }
+            eprintln!(
+                "warning: line {line}: range {start}-{end} is a duplicate of line {first_line}, skipped"
+            );
+            continue;
+        }
+        seen.insert((start, end), line);
+        ranges.insert(start, end);
     }
 
-    if let Some((current_start, current_end)) = current {
-        out += current_end - current_start + 1;
+    let mut ids = Vec::new();
+    for (line, text) in lines {
+        if text.is_empty() {
+            continue;
+        }
+        ids.push(text.parse().map_err(|_| InputError::Id {
+            line,
+            text: text.to_string(),
+        })?);
     }
 
-    Ok(Some(out))
+    Ok((ranges, ids))
+}
+
+fn parse(input: &str) -> Result<(RangeSet, Vec<u64>)> {
+    let lenient = std::env::var_os(LENIENT_ENV).is_some();
+    parse_database(input, lenient).context("failed to parse input")
+}
+
+pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u32>, anyhow::Error> {
+    let (ranges, mut ids) = parse(input)?;
+
+    Ok(Some(ranges.count_fresh(&mut ids) as u32))
+}
+
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
+    let (ranges, _) = parse(input)?;
+
+    Ok(Some(
+        ranges
+            .len()
+            .try_into()
+            .context("fresh id count does not fit in a u64")?,
+    ))
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_5 {
     use super::*;
+    use crate::test_rng::Rng;
+    use itertools::Itertools;
+
+    // Naive model of a RangeSet over 0..SIZE as a bitset.
+    const SIZE: u64 = 64;
+
+    fn to_bits(set: &RangeSet) -> Vec<bool> {
+        (0..SIZE).map(|v| set.contains(v)).collect()
+    }
+
+    fn random_set(rng: &mut Rng) -> (RangeSet, Vec<bool>) {
+        let mut next = || rng.below(SIZE);
+        let mut set = RangeSet::new();
+        let mut bits = vec![false; SIZE as usize];
+        for _ in 0..(next() % 6) {
+            let start = next();
+            let end = std::cmp::min(start + next() % 10, SIZE - 1);
+            set.insert(start, end);
+            for bit in &mut bits[start as usize..=end as usize] {
+                *bit = true;
+            }
+        }
+        (set, bits)
+    }
+
+    #[test]
+    fn test_range_set_matches_bitset() {
+        let mut rng = Rng::new(0x2025_0005);
+        for _ in 0..500 {
+            let (a, a_bits) = random_set(&mut rng);
+            let (b, b_bits) = random_set(&mut rng);
+
+            assert_eq!(to_bits(&a), a_bits);
+            assert_eq!(a.len(), a_bits.iter().filter(|b| **b).count() as u128);
+            assert_eq!(a.is_empty(), !a_bits.contains(&true));
+            // Normalised means sorted with a gap between every range
+            assert!(a.iter().tuple_windows().all(|(x, y)| x.1 + 1 < y.0));
+
+            let zip = |f: fn(bool, bool) -> bool| -> Vec<bool> {
+                a_bits
+                    .iter()
+                    .zip(b_bits.iter())
+                    .map(|(x, y)| f(*x, *y))
+                    .collect()
+            };
+            assert_eq!(to_bits(&a.union(&b)), zip(|x, y| x || y));
+            assert_eq!(to_bits(&a.intersection(&b)), zip(|x, y| x && y));
+            assert_eq!(to_bits(&a.difference(&b)), zip(|x, y| x && !y));
+
+            let complement = a.complement(10, 50);
+            let expected: Vec<bool> = (0..SIZE)
+                .map(|v| (10..=50).contains(&v) && !a_bits[v as usize])
+                .collect();
+            assert_eq!(to_bits(&complement), expected);
+        }
+    }
+
+    #[test]
+    fn test_range_set_edges() {
+        let mut set = RangeSet::new();
+        set.insert(u64::MAX - 1, u64::MAX);
+        set.insert(0, 0);
+        set.insert(1, 5);
+        assert_eq!(
+            set.iter().collect::<Vec<_>>(),
+            vec![(0, 5), (u64::MAX - 1, u64::MAX)]
+        );
+        assert!(set.contains(u64::MAX) && !set.contains(6));
+
+        let full = set.union(&set.complement(0, u64::MAX));
+        assert_eq!(full.iter().collect::<Vec<_>>(), vec![(0, u64::MAX)]);
+        assert_eq!(full.len(), 1 << 64);
+        assert!(full.complement(0, u64::MAX).is_empty());
+
+        set.insert(10, 3);
+        assert_eq!(set.len(), 8);
+    }
+
+    #[test]
+    fn test_match_ids() {
+        let set: RangeSet = [(3, 5), (10, 14), (16, 20), (12, 18)].into_iter().collect();
+        let ids = [32, 1, 5, 17, 8, 11, 3, 21];
+        assert_eq!(
+            set.match_ids(&ids),
+            vec![
+                None,
+                None,
+                Some((3, 5)),
+                Some((10, 20)),
+                None,
+                Some((10, 20)),
+                Some((3, 5)),
+                None
+            ]
+        );
+        assert_eq!(set.count_fresh(&mut ids.clone()), 4);
+        assert_eq!(set.count_fresh(&mut []), 0);
+        assert_eq!(RangeSet::new().count_fresh(&mut [1, 2, 3]), 0);
+    }
+
+    fn generate_lookup(ranges: usize, ids: usize) -> (Vec<(u64, u64)>, Vec<u64>) {
+        let mut rng = Rng::new(0x2025_0015);
+        let mut next = |max: u64| rng.below(max);
+        let ranges = (0..ranges)
+            .map(|_| {
+                let start = next(1 << 48);
+                (start, start + next(1 << 36))
+            })
+            .collect();
+        let ids = (0..ids).map(|_| next(1 << 48)).collect();
+        (ranges, ids)
+    }
+
+    // cargo test --release --bin 05 -- --ignored --nocapture
+    // AOC_DAY5_BENCH_IDS can be used to try other sizes.
+    #[test]
+    #[ignore]
+    fn bench_count_fresh() {
+        let id_count = std::env::var("AOC_DAY5_BENCH_IDS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(2_000_000);
+        let (ranges, ids) = generate_lookup(1000, id_count);
+        let set: RangeSet = ranges.iter().copied().collect();
+
+        let start = std::time::Instant::now();
+        let scan = ids
+            .iter()
+            .filter(|id| ranges.iter().any(|(s, e)| s <= *id && *id <= e))
+            .count();
+        let scan_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let search = ids.iter().filter(|id| set.contains(**id)).count();
+        let search_time = start.elapsed();
+
+        let mut sorted = ids.clone();
+        let start = std::time::Instant::now();
+        let sweep = set.count_fresh(&mut sorted);
+        let sweep_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let presorted = set.count_fresh(&mut sorted);
+        let presorted_time = start.elapsed();
+
+        assert_eq!(scan, search);
+        assert_eq!(scan, sweep);
+        assert_eq!(scan, presorted);
+        println!(
+            "{id_count} ids, {} ranges: {scan} fresh, linear scan {scan_time:?}, binary search {search_time:?}, sort + sweep {sweep_time:?}, presorted sweep {presorted_time:?}",
+            ranges.len()
+        );
+    }
+
+    #[test]
+    fn test_parse_database_errors() {
+        let err = |input: &str| parse_database(input, false).unwrap_err();
+        assert_eq!(
+            err("3-5\n20-10\n\n1"),
+            InputError::Reversed {
+                line: 2,
+                start: 20,
+                end: 10
+            }
+        );
+        assert_eq!(
+            err("3-5\n10-14\n3-5\n\n1"),
+            InputError::Duplicate {
+                line: 3,
+                first_line: 1,
+                start: 3,
+                end: 5
+            }
+        );
+        assert_eq!(
+            err("3-5\n10-14\n1\n5"),
+            InputError::MissingSeparator { line: 3 }
+        );
+        assert_eq!(err("3-5\n10-14"), InputError::MissingSeparator { line: 3 });
+        assert_eq!(
+            err("3-5\n10_14\n\n1"),
+            InputError::Range {
+                line: 2,
+                text: "10_14".to_string()
+            }
+        );
+        assert_eq!(
+            err("3-5\n\n1\n\nx"),
+            InputError::Id {
+                line: 5,
+                text: "x".to_string()
+            }
+        );
+        assert_eq!(
+            err("3-5\n20-10\n\n1").to_string(),
+            "line 2: range 20-10 is reversed"
+        );
+    }
+
+    #[test]
+    fn test_parse_database_lenient() -> anyhow::Result<()> {
+        let (ranges, ids) = parse_database("3-5\n20-10\n\n1\n12\n", true)?;
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![(3, 5), (10, 20)]);
+        assert_eq!(ids, vec![1, 12]);
+
+        // Once swapped it's the same range as 10-20, so it's skipped as a duplicate
+        let (ranges, ids) = parse_database("10-20\n20-10\n3-5\n3-5\n\n1", true)?;
+        assert_eq!(ranges.iter().collect::<Vec<_>>(), vec![(3, 5), (10, 20)]);
+        assert_eq!(ids, vec![1]);
+
+        // Strict mode still rejects it
+        assert_eq!(
+            parse_database("10-20\n20-10\n\n1", false),
+            Err(InputError::Reversed {
+                line: 2,
+                start: 20,
+                end: 10
+            })
+        );
+        Ok(())
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
@@ -6,141 +6,796 @@ use anyhow::{Context, Result, anyhow};
 use aoc_lib::parse::preamble::*;
 use std::str::FromStr;
 
-#[derive(Debug)]
-enum Operator {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
     Add,
     Mul,
+    Sub,
+    Div,
+    Max,
+    Min,
+}
+
+#[derive(Debug)]
+pub struct OperatorSpec {
+    pub operator: Operator,
+    pub glyph: &'static str,
+    // Value of a column with no numbers in it. For `-` and `/` it's only a right identity.
+    pub identity: i64,
+    // Non-associative operators are folded left to right in reading order.
+    pub associative: bool,
+}
+
+// Every operator the bottom row can use, looked up by glyph when parsing.
+pub const OPERATORS: [OperatorSpec; 6] = [
+    OperatorSpec {
+        operator: Operator::Add,
+        glyph: "+",
+        identity: 0,
+        associative: true,
+    },
+    OperatorSpec {
+        operator: Operator::Mul,
+        glyph: "*",
+        identity: 1,
+        associative: true,
+    },
+    OperatorSpec {
+        operator: Operator::Sub,
+        glyph: "-",
+        identity: 0,
+        associative: false,
+    },
+    OperatorSpec {
+        operator: Operator::Div,
+        glyph: "/",
+        identity: 1,
+        associative: false,
+    },
+    OperatorSpec {
+        operator: Operator::Max,
+        glyph: "max",
+        identity: i64::MIN,
+        associative: true,
+    },
+    OperatorSpec {
+        operator: Operator::Min,
+        glyph: "min",
+        identity: i64::MAX,
+        associative: true,
+    },
+];
+
+impl Operator {
+    pub fn spec(&self) -> &'static OperatorSpec {
+        OPERATORS
+            .iter()
+            .find(|spec| spec.operator == *self)
+            .expect("every operator has a spec")
+    }
+
+    pub fn apply<N: Number>(&self, lhs: &N, rhs: &N) -> Result<N> {
+        let out = match self {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Mul => lhs.checked_mul(rhs),
+            Operator::Sub => lhs.checked_sub(rhs),
+            Operator::Div => {
+                if *rhs == N::from(0) {
+                    return Err(anyhow!("division by zero computing {lhs} / {rhs}"));
+                }
+                lhs.checked_div(rhs)
+            }
+            Operator::Max => Some(std::cmp::max(lhs, rhs).clone()),
+            Operator::Min => Some(std::cmp::min(lhs, rhs).clone()),
+        };
+        out.with_context(|| format!("overflow computing {lhs} {} {rhs}", self.spec().glyph))
+    }
 }
 
 impl FromStr for Operator {
     type Err = anyhow::Error;
 
     fn from_str(input: &str) -> Result<Self> {
-        match input {
-            "+" => Ok(Operator::Add),
-            "*" => Ok(Operator::Mul),
-            other => Err(anyhow!("got unexpected operator {}", other)),
+        OPERATORS
+            .iter()
+            .find(|spec| spec.glyph == input)
+            .map(|spec| spec.operator)
+            .ok_or_else(|| anyhow!("got unexpected operator {}", input))
+    }
+}
+
+// Set to any value to do the sums with arbitrary precision and print the exact total.
+const BIGINT_ENV: &str = "AOC_DAY6_BIGINT";
+
+// Integer the worksheet can be evaluated in. Every operation is checked so overflow is an error
+// rather than a wrong answer.
+pub trait Number:
+    Sized + Clone + Ord + std::fmt::Display + FromStr<Err: std::fmt::Debug> + From<i64>
+{
+    fn checked_add(&self, rhs: &Self) -> Option<Self>;
+    fn checked_sub(&self, rhs: &Self) -> Option<Self>;
+    fn checked_mul(&self, rhs: &Self) -> Option<Self>;
+    // Truncates towards zero, None for division by zero or overflow
+    fn checked_div(&self, rhs: &Self) -> Option<Self>;
+}
+
+impl Number for i64 {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        i64::checked_add(*self, *rhs)
+    }
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        i64::checked_sub(*self, *rhs)
+    }
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        i64::checked_mul(*self, *rhs)
+    }
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        i64::checked_div(*self, *rhs)
+    }
+}
+
+// Signed decimal number of any size, only as fast as it needs to be for a worksheet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    // Least significant digit first with no trailing zeros, so 0 is empty and never negative
+    digits: Vec<u8>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut digits: Vec<u8>) -> Self {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Self {
+            negative: negative && !digits.is_empty(),
+            digits,
         }
     }
+
+    fn negate(&self) -> Self {
+        Self::from_parts(!self.negative, self.digits.clone())
+    }
 }
 
-pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<i64>, anyhow::Error> {
-    let mut lines: Vec<&str> =
-        parse_input(LineSplitter, Identity, input).context("failed to parse input")?;
+fn cmp_magnitude(lhs: &[u8], rhs: &[u8]) -> std::cmp::Ordering {
+    lhs.len()
+        .cmp(&rhs.len())
+        .then_with(|| lhs.iter().rev().cmp(rhs.iter().rev()))
+}
 
-    let operators = lines
-        .pop()
-        .context("required to have 1 operators row at the end")?;
-    let operators: Vec<Operator> = operators
-        .split_ascii_whitespace()
-        .map(|o| o.parse())
-        .collect::<Result<Vec<Operator>>>()
-        .context("failed to parse operators")?;
+fn add_magnitude(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(std::cmp::max(lhs.len(), rhs.len()) + 1);
+    let mut carry = 0;
+    for idx in 0..std::cmp::max(lhs.len(), rhs.len()) {
+        let sum = lhs.get(idx).unwrap_or(&0) + rhs.get(idx).unwrap_or(&0) + carry;
+        out.push(sum % 10);
+        carry = sum / 10;
+    }
+    if carry > 0 {
+        out.push(carry);
+    }
+    out
+}
 
-    let mut table = Vec::new();
-    for line in lines {
-        let mut fields: Vec<i64> = Vec::new();
-        for field in line.split_ascii_whitespace() {
-            fields.push(field.parse().context("failed to parse input number")?);
+// lhs must not be smaller than rhs
+fn sub_magnitude(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(lhs.len());
+    let mut borrow = 0;
+    for (idx, digit) in lhs.iter().enumerate() {
+        let take = rhs.get(idx).unwrap_or(&0) + borrow;
+        if *digit >= take {
+            out.push(digit - take);
+            borrow = 0;
+        } else {
+            out.push(digit + 10 - take);
+            borrow = 1;
         }
+    }
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
 
-        table.push(fields);
+fn mul_magnitude(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut acc = vec![0_u64; lhs.len() + rhs.len()];
+    for (i, a) in lhs.iter().enumerate() {
+        for (j, b) in rhs.iter().enumerate() {
+            acc[i + j] += u64::from(*a) * u64::from(*b);
+        }
+    }
+    let mut out = Vec::with_capacity(acc.len());
+    let mut carry = 0;
+    for value in acc {
+        let value = value + carry;
+        out.push((value % 10) as u8);
+        carry = value / 10;
+    }
+    while carry > 0 {
+        out.push((carry % 10) as u8);
+        carry /= 10;
     }
+    out
+}
 
-    let mut iters = Vec::new();
-    for row in &table {
-        iters.push(row.iter());
+// Long division, one quotient digit at a time from the top. rhs must not be zero.
+fn div_magnitude(lhs: &[u8], rhs: &[u8]) -> Vec<u8> {
+    let mut quotient = vec![0; lhs.len()];
+    let mut remainder: Vec<u8> = Vec::new();
+    for (idx, digit) in lhs.iter().enumerate().rev() {
+        remainder.insert(0, *digit);
+        while remainder.last() == Some(&0) {
+            remainder.pop();
+        }
+        while cmp_magnitude(&remainder, rhs).is_ge() {
+            remainder = sub_magnitude(&remainder, rhs);
+            quotient[idx] += 1;
+        }
+    }
+    quotient
+}
+
+impl Number for BigInt {
+    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        if self.negative == rhs.negative {
+            return Some(Self::from_parts(
+                self.negative,
+                add_magnitude(&self.digits, &rhs.digits),
+            ));
+        }
+        Some(match cmp_magnitude(&self.digits, &rhs.digits) {
+            std::cmp::Ordering::Less => {
+                Self::from_parts(rhs.negative, sub_magnitude(&rhs.digits, &self.digits))
+            }
+            _ => Self::from_parts(self.negative, sub_magnitude(&self.digits, &rhs.digits)),
+        })
+    }
+    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        self.checked_add(&rhs.negate())
+    }
+    fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        Some(Self::from_parts(
+            self.negative != rhs.negative,
+            mul_magnitude(&self.digits, &rhs.digits),
+        ))
+    }
+    fn checked_div(&self, rhs: &Self) -> Option<Self> {
+        if rhs.digits.is_empty() {
+            return None;
+        }
+        Some(Self::from_parts(
+            self.negative != rhs.negative,
+            div_magnitude(&self.digits, &rhs.digits),
+        ))
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(other: i64) -> Self {
+        let mut magnitude = other.unsigned_abs();
+        let mut digits = Vec::new();
+        while magnitude > 0 {
+            digits.push((magnitude % 10) as u8);
+            magnitude /= 10;
+        }
+        Self::from_parts(other < 0, digits)
     }
+}
+
+impl FromStr for BigInt {
+    type Err = anyhow::Error;
 
-    let mut out = 0;
-    for op in &operators {
-        let mut value = match op {
-            Operator::Add => 0,
-            Operator::Mul => 1,
+    fn from_str(input: &str) -> Result<Self> {
+        let (negative, body) = match input.strip_prefix('-') {
+            Some(body) => (true, body),
+            None => (false, input),
         };
-        for iter in &mut iters {
-            let column_value = iter
-                .next()
-                .context("expected to get next column from iter")?;
-            value = match op {
-                Operator::Add => value + column_value,
-                Operator::Mul => value * column_value,
-            };
+        if body.is_empty() || !body.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(anyhow!("expected an integer but found {input:?}"));
         }
-        out += value;
+        Ok(Self::from_parts(
+            negative,
+            body.bytes().rev().map(|b| b - b'0').collect(),
+        ))
     }
+}
 
-    Ok(Some(out))
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => std::cmp::Ordering::Greater,
+            (true, false) => std::cmp::Ordering::Less,
+            (false, false) => cmp_magnitude(&self.digits, &other.digits),
+            (true, true) => cmp_magnitude(&other.digits, &self.digits),
+        }
+    }
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.digits.is_empty() {
+            return write!(f, "0");
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        for d in self.digits.iter().rev() {
+            write!(f, "{d}")?;
+        }
+        Ok(())
+    }
+}
+
+// Applies the operator across one problem's numbers, given in reading order. Folds from the first
+// number so the i64 identity never clamps a BigInt, it's only the value of an empty column.
+pub fn evaluate<N: Number>(op: Operator, numbers: &[N]) -> Result<N> {
+    match numbers.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .try_fold(first.clone(), |acc, n| op.apply(&acc, n)),
+        None => Ok(N::from(op.spec().identity)),
+    }
+}
+
+fn add_problem<N: Number>(total: &N, op: Operator, numbers: &[N], problem: usize) -> Result<N> {
+    evaluate(op, numbers)
+        .and_then(|value| Operator::Add.apply(total, &value))
+        .with_context(|| format!("failed to evaluate problem {problem}"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem<N> {
+    pub numbers_rowwise: Vec<N>,
+    // One number per column from right to left, with the digits read top to bottom
+    pub numbers_columnwise: Vec<N>,
+    pub operator: Operator,
+    // Columns of the worksheet the problem covers
+    pub span: std::ops::Range<usize>,
+}
+
+// Lines of the worksheet as chars, all padded to the same width since editors often strip
+// trailing spaces. The operator row is last.
+fn pad_worksheet(input: &str) -> Result<Vec<Vec<char>>> {
     let mut lines: Vec<&str> =
         parse_input(LineSplitter, Identity, input).context("failed to parse input")?;
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+    if lines.is_empty() {
+        return Err(anyhow!("required to have 1 operators row at the end"));
+    }
+
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    Ok(lines
+        .iter()
+        .map(|line| {
+            let mut out: Vec<char> = line.chars().collect();
+            out.resize(width, ' ');
+            out
+        })
+        .collect())
+}
 
-    let operators = lines
-        .pop()
-        .context("required to have 1 operators row at the end")?;
-
-    let mut iters = Vec::new();
-    for line in &lines {
-        iters.push(line.chars().rev());
-    }
-
-    let mut out = 0;
-    let mut col = Vec::new();
-    for op_char in operators.chars().rev() {
-        let mut value = 0;
-        let mut empty = true;
-        for iter in &mut iters {
-            let column_char = iter
-                .next()
-                .context("expected to get next column from iter")?;
-            if column_char == ' ' {
+// Splits the worksheet into problems, left to right, separated by all blank columns.
+pub fn parse_worksheet<N: Number>(input: &str) -> Result<Vec<Problem<N>>> {
+    let lines = pad_worksheet(input)?;
+    let (operators, rows) = lines.split_last().expect("pad_worksheet is never empty");
+    let width = operators.len();
+
+    let blank = |col: usize| operators[col] == ' ' && rows.iter().all(|row| row[col] == ' ');
+    let mut spans = Vec::new();
+    let mut start = None;
+    for col in 0..=width {
+        match (start, col < width && !blank(col)) {
+            (None, true) => start = Some(col),
+            (Some(from), false) => {
+                spans.push(from..col);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    spans
+        .into_iter()
+        .enumerate()
+        .map(|(idx, span)| {
+            read_problem(rows, operators, span)
+                .with_context(|| format!("failed to read problem {idx}"))
+        })
+        .collect()
+}
+
+fn read_problem<N: Number>(
+    rows: &[Vec<char>],
+    operators: &[char],
+    span: std::ops::Range<usize>,
+) -> Result<Problem<N>> {
+    let text = |row: &[char]| -> String {
+        row[span.clone()]
+            .iter()
+            .collect::<String>()
+            .trim()
+            .to_string()
+    };
+
+    let operator = text(operators).parse()?;
+
+    let mut numbers_rowwise = Vec::new();
+    for row in rows {
+        let field = text(row);
+        if field.is_empty() {
+            continue;
+        }
+        numbers_rowwise.push(
+            field
+                .parse()
+                .map_err(|e| anyhow!("{e:?}"))
+                .with_context(|| format!("failed to parse input number {field:?}"))?,
+        );
+    }
+
+    let mut numbers_columnwise = Vec::new();
+    for col in span.clone().rev() {
+        let mut value = None;
+        for row in rows {
+            if row[col] == ' ' {
                 continue;
             }
-            empty = false;
-            let digit: u64 = column_char
+            let digit: i64 = row[col]
                 .to_digit(10)
                 .context("failed to convert digit to int")?
                 .into();
-
-            value *= 10;
-            value += digit;
+            value = Some(
+                value
+                    .unwrap_or_else(|| N::from(0))
+                    .checked_mul(&N::from(10))
+                    .and_then(|value| value.checked_add(&N::from(digit)))
+                    .context("overflow reading a number")?,
+            );
         }
-        if empty {
-            continue;
+        // Only the operator is in this column
+        if let Some(value) = value {
+            numbers_columnwise.push(value);
         }
-        col.push(value);
+    }
 
-        let op = match op_char {
-            ' ' => continue,
-            '+' => Operator::Add,
-            '*' => Operator::Mul,
-            other => {
-                return Err(anyhow!("got unexpected operator char: {}", other));
-            }
-        };
+    Ok(Problem {
+        numbers_rowwise,
+        numbers_columnwise,
+        operator,
+        span,
+    })
+}
 
-        let mut total = match op {
-            Operator::Add => 0,
-            Operator::Mul => 1,
-        };
-        for column_value in col {
-            total = match op {
-                Operator::Add => total + column_value,
-                Operator::Mul => total * column_value,
-            };
+fn total<N: Number>(input: &str, numbers: impl Fn(Problem<N>) -> Vec<N>) -> Result<N> {
+    let mut out = N::from(0);
+    for (idx, problem) in parse_worksheet(input)?.into_iter().enumerate() {
+        let op = problem.operator;
+        out = add_problem(&out, op, &numbers(problem), idx)?;
+    }
+    Ok(out)
+}
+
+fn total_rowwise<N: Number>(input: &str) -> Result<N> {
+    total(input, |problem| problem.numbers_rowwise)
+}
+
+fn total_columnwise<N: Number>(input: &str) -> Result<N> {
+    total(input, |problem| problem.numbers_columnwise)
+}
+
+// Set to any value to print how every problem in the worksheet was read.
+const DEBUG_ENV: &str = "AOC_DAY6_DEBUG";
+
+fn describe<N: Number>(op: Operator, numbers: &[N]) -> String {
+    let sum = numbers
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(&format!(" {} ", op.spec().glyph));
+    match evaluate(op, numbers) {
+        Ok(value) => format!("{sum} = {value}"),
+        Err(e) => format!("{sum} = error: {e}"),
+    }
+}
+
+// Each problem's block of the worksheet next to the numbers read from it both ways and their
+// subtotals. Uses exact arithmetic so a subtotal is always shown even if it would overflow.
+pub fn render_worksheet(input: &str) -> Result<String> {
+    let lines = pad_worksheet(input)?;
+    let problems = parse_worksheet::<BigInt>(input)?;
+
+    let mut out = String::new();
+    for (idx, problem) in problems.iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let info = [
+            format!("problem {idx}, columns {:?}", problem.span),
+            format!(
+                "row-wise:    {}",
+                describe(problem.operator, &problem.numbers_rowwise)
+            ),
+            format!(
+                "column-wise: {}",
+                describe(problem.operator, &problem.numbers_columnwise)
+            ),
+        ];
+        let blank = vec![' '; problem.span.len()];
+        for row in 0..std::cmp::max(lines.len(), info.len()) {
+            let block: String = lines
+                .get(row)
+                .map_or(&blank[..], |line| &line[problem.span.clone()])
+                .iter()
+                .collect();
+            let line = format!("{block} | {}", info.get(row).map_or("", String::as_str));
+            out.push_str(line.trim_end());
+            out.push('\n');
         }
-        out += total;
-        col = Vec::new();
     }
+    Ok(out)
+}
 
-    Ok(Some(out))
+// Uses checked i64 maths, or with BIGINT_ENV set works it out exactly and prints it before
+// narrowing to the answer type. A total that overflows i64 but may still fit the answer type,
+// like part two's u64, is worked out exactly before giving up.
+fn run<T>(
+    input: &str,
+    total: fn(&str) -> Result<i64>,
+    exact: fn(&str) -> Result<BigInt>,
+) -> Result<T>
+where
+    T: TryFrom<i64> + FromStr,
+{
+    if std::env::var_os(DEBUG_ENV).is_some() {
+        print!("{}", render_worksheet(input)?);
+    }
+    let narrow = |out: BigInt| {
+        out.to_string()
+            .parse()
+            .map_err(|_| anyhow!("total {out} does not fit in the answer type"))
+    };
+    if std::env::var_os(BIGINT_ENV).is_some() {
+        let out = exact(input)?;
+        println!("exact total: {out}");
+        return narrow(out);
+    }
+    if let Ok(out) = total(input)
+        && let Ok(out) = T::try_from(out)
+    {
+        return Ok(out);
+    }
+    narrow(exact(input)?)
+}
+
+pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<i64>, anyhow::Error> {
+    run(input, total_rowwise, total_rowwise).map(Some)
+}
+
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
+    run(input, total_columnwise, total_columnwise).map(Some)
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_6 {
     use super::*;
+    use crate::test_rng::Rng;
+
+    #[test]
+    fn test_evaluate() -> anyhow::Result<()> {
+        for spec in &OPERATORS {
+            assert_eq!(spec.glyph.parse::<Operator>()?, spec.operator);
+            assert_eq!(evaluate::<i64>(spec.operator, &[])?, spec.identity);
+            assert_eq!(evaluate::<i64>(spec.operator, &[7])?, 7);
+            assert_eq!(spec.operator.apply(&7, &spec.identity)?, 7);
+        }
+        assert!("%".parse::<Operator>().is_err());
+
+        assert_eq!(evaluate::<i64>(Operator::Add, &[1, 2, 3])?, 6);
+        assert_eq!(evaluate::<i64>(Operator::Mul, &[2, 3, 4])?, 24);
+        assert_eq!(evaluate::<i64>(Operator::Sub, &[10, 3, 2])?, 5);
+        assert_eq!(evaluate::<i64>(Operator::Div, &[100, 5, 2])?, 10);
+        assert_eq!(evaluate::<i64>(Operator::Max, &[4, 9, 2])?, 9);
+        assert_eq!(evaluate::<i64>(Operator::Min, &[4, 9, 2])?, 2);
+        assert!(evaluate::<i64>(Operator::Div, &[1, 0]).is_err());
+
+        // The i64 identities of min and max mustn't clamp numbers outside the i64 range
+        let huge: Vec<BigInt> = vec![
+            "99999999999999999999".parse()?,
+            "99999999999999999998".parse()?,
+        ];
+        assert_eq!(evaluate(Operator::Min, &huge)?, huge[1]);
+        assert_eq!(evaluate(Operator::Max, &huge)?, huge[0]);
+        let tiny: Vec<BigInt> = vec![
+            "-99999999999999999999".parse()?,
+            "-99999999999999999998".parse()?,
+        ];
+        assert_eq!(evaluate(Operator::Max, &tiny)?, tiny[1]);
+        assert_eq!(evaluate(Operator::Min, &tiny)?, tiny[0]);
+        assert_eq!(
+            total_rowwise::<BigInt>("99999999999999999999\n99999999999999999998\nmin\n")?,
+            huge[1]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_more_operators() -> anyhow::Result<()> {
+        let input = "123 982 123 456\n 45   4  45  78\n  6   2   6   9\n-   /   max min\n";
+        assert_eq!(
+            part_one(input, RunType::Example)?,
+            Some((123 - 45 - 6) + (982 / 4 / 2) + 123 + 9)
+        );
+        // Column-wise the numbers are read right to left, so the first problem is 356 - 24 - 1
+        assert_eq!(
+            part_two(input, RunType::Example)?,
+            Some((356 - 24 - 1) + (242 / 8 / 9) + 356 + 4)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_int_matches_i128() -> anyhow::Result<()> {
+        let mut rng = Rng::new(0x2025_0006);
+        let mut next = || {
+            let bits = rng.next_u64();
+            // Mix of tiny and huge values, either sign
+            let value = (bits >> 1) as i64 >> ((bits >> 7) % 64);
+            if bits & 1 == 0 { value } else { -value }
+        };
+        for _ in 0..2000 {
+            let (a, b) = (next(), next());
+            let (big_a, big_b) = (BigInt::from(a), BigInt::from(b));
+            let (wide_a, wide_b) = (i128::from(a), i128::from(b));
+
+            assert_eq!(big_a.to_string(), a.to_string());
+            assert_eq!(big_a.to_string().parse::<BigInt>()?, big_a);
+            assert_eq!(big_a.cmp(&big_b), a.cmp(&b), "{a} vs {b}");
+            for (op, expected) in [
+                (Operator::Add, Some(wide_a + wide_b)),
+                (Operator::Sub, Some(wide_a - wide_b)),
+                (Operator::Mul, Some(wide_a * wide_b)),
+                (Operator::Div, wide_a.checked_div(wide_b)),
+            ] {
+                match expected {
+                    Some(expected) => {
+                        assert_eq!(op.apply(&big_a, &big_b)?.to_string(), expected.to_string())
+                    }
+                    None => assert!(op.apply(&big_a, &big_b).is_err()),
+                }
+            }
+        }
+
+        assert_eq!("-0".parse::<BigInt>()?, BigInt::default());
+        assert!("".parse::<BigInt>().is_err());
+        assert!("1-2".parse::<BigInt>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_overflow() -> anyhow::Result<()> {
+        // Totals past i64::MAX still fit part two's u64 answer, whether it's a number that
+        // overflows as it's read or the sum of the problems
+        let input = format!("{}+\n", "9\n".repeat(19));
+        assert_eq!(
+            part_two(&input, RunType::Example)?,
+            Some(9999999999999999999)
+        );
+        let input = format!("{}+ +\n", "6 6\n".repeat(19));
+        assert_eq!(
+            part_two(&input, RunType::Example)?,
+            Some(13333333333333333332)
+        );
+        let input = format!("{}+\n", "9\n".repeat(20));
+        assert_eq!(
+            format!("{:#}", part_two(&input, RunType::Example).unwrap_err()),
+            format!("total {} does not fit in the answer type", "9".repeat(20))
+        );
+
+        let input = "1 99999999999 2\n2 99999999999 3\n+ *           +\n";
+        let err = total_rowwise::<i64>(input).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "failed to evaluate problem 1: overflow computing 99999999999 * 99999999999"
+        );
+        assert_eq!(
+            total_rowwise::<BigInt>(input)?.to_string(),
+            "9999999999800000000009"
+        );
+
+        // Twenty nines in a column don't fit in an i64 as soon as they are read
+        let input = format!("{}\n*\n", vec!["9"; 20].join("\n"));
+        let err = total_columnwise::<i64>(&input).unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "failed to read problem 0: overflow reading a number"
+        );
+        assert_eq!(
+            total_columnwise::<BigInt>(&input)?.to_string(),
+            "9".repeat(20)
+        );
+
+        assert_eq!(
+            total_rowwise::<BigInt>(&advent_of_code::template::read_file_part(
+                "examples", DAY, 1
+            ))?,
+            BigInt::from(4277556)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_worksheet() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 1);
+        let problems = parse_worksheet::<i64>(input)?;
+        assert_eq!(problems.len(), 4);
+        assert_eq!(
+            problems[0],
+            Problem {
+                numbers_rowwise: vec![123, 45, 6],
+                numbers_columnwise: vec![356, 24, 1],
+                operator: Operator::Mul,
+                span: 0..3,
+            }
+        );
+        assert_eq!(
+            problems[3],
+            Problem {
+                numbers_rowwise: vec![64, 23, 314],
+                numbers_columnwise: vec![4, 431, 623],
+                operator: Operator::Add,
+                span: 12..15,
+            }
+        );
+
+        // Same worksheet with the trailing spaces stripped and a trailing blank line
+        let ragged: String = input
+            .lines()
+            .map(|line| format!("{}\n", line.trim_end()))
+            .collect::<String>()
+            + "\n";
+        assert_eq!(parse_worksheet::<i64>(&ragged)?, problems);
+        assert_eq!(total_columnwise::<i64>(&ragged)?, 3263827);
+
+        // An operator wider than its numbers still belongs to the problem
+        let problems = parse_worksheet::<i64>("  1 2\n 34 5\nmax +\n")?;
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].operator, Operator::Max);
+        assert_eq!(problems[0].numbers_rowwise, vec![1, 34]);
+        assert_eq!(problems[0].numbers_columnwise, vec![14, 3]);
+        assert_eq!(problems[1].span, 4..5);
+
+        assert!(parse_worksheet::<i64>("1 2\n+ ?\n").is_err());
+        assert!(parse_worksheet::<i64>("").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_worksheet() -> anyhow::Result<()> {
+        // Golden files are in data/golden, regenerate with AOC_DAY6_DEBUG=1 if the format changes
+        for part in [1, 2] {
+            let input = &advent_of_code::template::read_file_part("examples", DAY, part);
+            let golden = advent_of_code::template::read_file_part("golden", DAY, part);
+            assert_eq!(render_worksheet(input)?, golden, "example {part}");
+        }
+        Ok(())
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
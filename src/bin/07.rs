@@ -6,14 +6,17 @@ use aoc_lib::grid::{CountingMap, Direction, Location, Map};
 use anyhow::{Context, Result, anyhow};
 
 #[derive(Debug, Clone, PartialEq)]
-enum Tile {
+pub enum Tile {
     Start,
     Empty,
     Splitter,
+    // `/`
+    MirrorForward,
+    // `\`
+    MirrorBack,
+    Absorber,
 }
 
-impl Tile {}
-
 impl TryFrom<char> for Tile {
     type Error = anyhow::Error;
 
@@ -22,6 +25,9 @@ impl TryFrom<char> for Tile {
             'S' => Tile::Start,
             '.' => Tile::Empty,
             '^' => Tile::Splitter,
+            '/' => Tile::MirrorForward,
+            '\\' => Tile::MirrorBack,
+            '#' => Tile::Absorber,
             other => {
                 return Err(anyhow!("found unexpected tile value {:?}", other));
             }
@@ -29,38 +35,311 @@ impl TryFrom<char> for Tile {
     }
 }
 
+impl Tile {
+    fn glyph(&self) -> char {
+        match self {
+            Tile::Start => 'S',
+            Tile::Empty => '.',
+            Tile::Splitter => '^',
+            Tile::MirrorForward => '/',
+            Tile::MirrorBack => '\\',
+            Tile::Absorber => '#',
+        }
+    }
+}
+
+// Outgoing beams from a tile as (step, heading) pairs: the beam moves one step in `step` and
+// then carries on travelling `heading`. No pairs means the beam was absorbed.
+pub type Emission = Vec<(Direction, Direction)>;
+
+// Where a beam travelling `heading` goes after bouncing off a `/` mirror.
+fn reflect_forward(heading: &Direction) -> Direction {
+    match heading {
+        Direction::North => Direction::East,
+        Direction::East => Direction::North,
+        Direction::South => Direction::West,
+        Direction::West => Direction::South,
+    }
+}
+
+// Same for a `\` mirror.
+fn reflect_back(heading: &Direction) -> Direction {
+    match heading {
+        Direction::North => Direction::West,
+        Direction::West => Direction::North,
+        Direction::South => Direction::East,
+        Direction::East => Direction::South,
+    }
+}
+
+fn heading_index(heading: &Direction) -> usize {
+    match heading {
+        Direction::North => 0,
+        Direction::East => 1,
+        Direction::South => 2,
+        Direction::West => 3,
+    }
+}
+
+// Standard tile behaviours, with splitters emitting `splitter` whichever way the beam came in.
+pub fn tile_behaviour(splitter: Emission) -> impl Fn(&Tile, &Direction) -> Emission {
+    move |tile, heading| match tile {
+        Tile::Start | Tile::Empty => vec![(heading.clone(), heading.clone())],
+        Tile::Splitter => splitter.clone(),
+        Tile::MirrorForward => {
+            let heading = reflect_forward(heading);
+            vec![(heading.clone(), heading)]
+        }
+        Tile::MirrorBack => {
+            let heading = reflect_back(heading);
+            vec![(heading.clone(), heading)]
+        }
+        Tile::Absorber => vec![],
+    }
+}
+
+// Timelines ended by a beam whose next step of `step` would leave the map. Only a beam travelling
+// that way ends one, one a splitter sends sideways off the edge is lost.
+fn leaves_map_timelines(step: &Direction, heading: &Direction) -> u128 {
+    u128::from(heading_index(step) == heading_index(heading))
+}
+
+// The tachyon manifold: beams fall south and a splitter sends them down either side of it.
+fn tachyon_splitter() -> Emission {
+    vec![
+        (Direction::East, Direction::South),
+        (Direction::West, Direction::South),
+    ]
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Visit {
+    New,
+    Active,
+    Done(u128),
+}
+
+pub struct BeamEngine<F> {
+    behaviour: F,
+}
+
+impl<F> BeamEngine<F>
+where
+    F: Fn(&Tile, &Direction) -> Emission,
+{
+    pub fn new(behaviour: F) -> Self {
+        Self { behaviour }
+    }
+
+    // Every location a beam crossed. Each (location, heading) state is only followed once so
+    // beams that merge or go round in a loop still finish.
+    pub fn trace(&self, map: &Map<Tile>, start: Location, heading: Direction) -> CountingMap {
+        let mut seen = map.transform(|_, _| [false; 4]);
+        let mut visited = CountingMap::from(map);
+        let mut to_process = vec![(start, heading)];
+
+        while let Some((current, heading)) = to_process.pop() {
+            let state = &mut seen.get_mut(&current)[heading_index(&heading)];
+            if *state {
+                continue;
+            }
+            *state = true;
+            visited.mark(&current);
+
+            for (step, next_heading) in (self.behaviour)(map.get(&current), &heading) {
+                if let Some(next) = map.go_direction(&current, &step) {
+                    to_process.push((next, next_heading));
+                }
+            }
+        }
+
+        visited
+    }
+
+    // Number of distinct paths a single particle could take, where a beam that travels off the
+    // map or is absorbed ends one timeline. A beam a splitter steps sideways off the map is lost,
+    // like the original part two. Fails if a path can go round forever.
+    pub fn count_timelines(
+        &self,
+        map: &Map<Tile>,
+        start: Location,
+        heading: Direction,
+    ) -> Result<u128> {
+        let root = (start.clone(), heading_index(&heading));
+        let mut visits = map.transform(|_, _| [Visit::New; 4]);
+        // Depth first without recursion, each state is pushed again once its children are done
+        let mut stack = vec![(start, heading, false)];
+
+        while let Some((current, heading, expanded)) = stack.pop() {
+            let idx = heading_index(&heading);
+            let emission = (self.behaviour)(map.get(&current), &heading);
+
+            if expanded {
+                let mut count: u128 = if emission.is_empty() { 1 } else { 0 };
+                for (step, next_heading) in &emission {
+                    let paths = match map.go_direction(&current, step) {
+                        None => leaves_map_timelines(step, next_heading),
+                        Some(next) => match visits.get(&next)[heading_index(next_heading)] {
+                            Visit::Done(paths) => paths,
+                            _ => unreachable!("children are finished before their parent"),
+                        },
+                    };
+                    count = count
+                        .checked_add(paths)
+                        .with_context(|| format!("timeline count overflowed at {current:?}"))?;
+                }
+                visits.get_mut(&current)[idx] = Visit::Done(count);
+                continue;
+            }
+
+            match visits.get(&current)[idx] {
+                Visit::Done(_) => continue,
+                Visit::Active => return Err(anyhow!("beam loops forever through {current:?}")),
+                Visit::New => {}
+            }
+            visits.get_mut(&current)[idx] = Visit::Active;
+            stack.push((current.clone(), heading, true));
+
+            for (step, next_heading) in emission {
+                if let Some(next) = map.go_direction(&current, &step) {
+                    match visits.get(&next)[heading_index(&next_heading)] {
+                        Visit::New => stack.push((next, next_heading, false)),
+                        Visit::Active => {
+                            return Err(anyhow!("beam loops forever through {next:?}"));
+                        }
+                        Visit::Done(_) => {}
+                    }
+                }
+            }
+        }
+
+        match visits.get(&root.0)[root.1] {
+            Visit::Done(paths) => Ok(paths),
+            _ => unreachable!("the start is always finished"),
+        }
+    }
+}
+
+// Set to any value to print the manifold with the beams and splitters marked.
+const OVERLAY_ENV: &str = "AOC_DAY7_OVERLAY";
+// Set to a file name to write the same overlay as an SVG, better for big manifolds.
+const OVERLAY_SVG_ENV: &str = "AOC_DAY7_OVERLAY_SVG";
+
+// Set to any value to print how many timelines there are after each row.
+const TIMELINES_ENV: &str = "AOC_DAY7_TIMELINES";
+
+// Each SVG cell is this many pixels square
+const SVG_SCALE: usize = 4;
+
+// Cells a beam crossed are `|`, splitters it hit are bold green and ones it never reached are
+// bold red.
+pub fn render_overlay(map: &Map<Tile>, seen: &CountingMap) -> String {
+    let mut out = String::new();
+    for row in map.iter() {
+        for (location, tile) in row {
+            match (tile, seen.get(&location)) {
+                (Tile::Splitter, true) => out.push_str("\x1b[1;32m^\x1b[0m"),
+                (Tile::Splitter, false) => out.push_str("\x1b[1;31m^\x1b[0m"),
+                (Tile::Empty, true) => out.push('|'),
+                (tile, _) => out.push(tile.glyph()),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Same colours as render_overlay. Beam cells are merged into vertical runs so the file stays
+// small for real inputs.
+pub fn render_svg(map: &Map<Tile>, seen: &CountingMap) -> Result<String> {
+    use std::fmt::Write;
+
+    let height = map.0.len();
+    let width = map.0.first().map(|row| row.len()).unwrap_or(0);
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {width} {height}\" \
+         width=\"{}\" height=\"{}\" shape-rendering=\"crispEdges\">",
+        width * SVG_SCALE,
+        height * SVG_SCALE
+    )?;
+    writeln!(
+        out,
+        "<rect width=\"{width}\" height=\"{height}\" fill=\"#0f0f23\"/>"
+    )?;
+
+    for col in 0..width {
+        let mut row = 0;
+        while row < height {
+            if !seen.get(&Location(row, col)) {
+                row += 1;
+                continue;
+            }
+            let start = row;
+            while row < height && seen.get(&Location(row, col)) {
+                row += 1;
+            }
+            writeln!(
+                out,
+                "<rect x=\"{col}\" y=\"{start}\" width=\"1\" height=\"{}\" fill=\"#ffff66\"/>",
+                row - start
+            )?;
+        }
+    }
+
+    for (location, tile) in map.iter().flatten() {
+        let fill = match (tile, seen.get(&location)) {
+            (Tile::Splitter, true) => "#00cc00",
+            (Tile::Splitter, false) => "#ff0000",
+            (Tile::Empty, _) => continue,
+            (Tile::Start, _) => "#ffffff",
+            _ => "#888888",
+        };
+        writeln!(
+            out,
+            "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{fill}\"/>",
+            location.1, location.0
+        )?;
+    }
+
+    out.push_str("</svg>\n");
+    Ok(out)
+}
+
 pub fn part_one(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
     let map: Map<Tile> = Map::try_from(input).context("Failed to parse input")?;
     let start = map
         .find(|(_, value)| *value == &Tile::Start)
         .context("expected to find start tile")?;
 
-    let mut seen = CountingMap::from(&map);
-    let mut to_process = vec![start];
+    let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+    let seen = engine.trace(&map, start, Direction::South);
 
-    let mut out = 0;
-    while let Some(current) = to_process.pop() {
-        if seen.get(&current) {
-            continue;
-        }
-        seen.mark(&current);
+    let (hit, missed): (Vec<_>, Vec<_>) = map
+        .iter()
+        .flatten()
+        .filter(|(_, tile)| **tile == Tile::Splitter)
+        .partition(|(location, _)| seen.get(location));
 
-        if map.get(&current) == &Tile::Splitter {
-            out += 1;
-            if let Some(next) = map.go_direction(&current, &Direction::East) {
-                to_process.push(next);
-            }
-            if let Some(next) = map.go_direction(&current, &Direction::West) {
-                to_process.push(next);
-            }
-        } else if let Some(next) = map.go_direction(&current, &Direction::South) {
-            to_process.push(next);
-        }
+    if std::env::var_os(OVERLAY_ENV).is_some() {
+        print!("{}", render_overlay(&map, &seen));
+        println!(
+            "{} splitters hit, {} never reached",
+            hit.len(),
+            missed.len()
+        );
+    }
+    if let Some(path) = std::env::var_os(OVERLAY_SVG_ENV) {
+        let svg = render_svg(&map, &seen).context("failed to render overlay SVG")?;
+        std::fs::write(&path, svg).with_context(|| format!("failed to write {path:?}"))?;
     }
 
-    Ok(Some(out))
+    Ok(Some(hit.len() as u64))
 }
 
+// Only used to cross-check sweep_timelines in tests
+#[cfg(test)]
 fn cached_go_path(map: &Map<Tile>, mut current: Location, cache: &mut Map<Option<u64>>) -> u64 {
     if let Some(cached) = cache.get(&current) {
         return *cached;
@@ -94,19 +373,298 @@ fn cached_go_path(map: &Map<Tile>, mut current: Location, cache: &mut Map<Option
     count
 }
 
-pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sweep {
+    pub timelines: u128,
+    // Timelines alive in each row once its splitters have been applied, 0 above the start
+    pub per_row: Vec<u128>,
+}
+
+fn add_timelines(row: usize, target: &mut u128, count: u128) -> Result<()> {
+    *target = target
+        .checked_add(count)
+        .with_context(|| format!("timeline count overflowed in row {row}"))?;
+    Ok(())
+}
+
+// Counts timelines a row at a time, only keeping how many beams are in each column. Splits use
+// tachyon_splitter, go_direction and leaves_map_timelines like BeamEngine so the two agree on
+// splitters at the edges. A split beam always carries on south.
+pub fn sweep_timelines(map: &Map<Tile>, start: &Location) -> Result<Sweep> {
+    let width = map.0.first().map_or(0, |row| row.len());
+    let mut counts = vec![0_u128; width];
+    counts[start.1] = 1;
+    // Timelines ended by beams going over the side, none with leaves_map_timelines as it is
+    let mut ended_at_side: u128 = 0;
+
+    let unsupported = |tile: &Tile, location: Location| {
+        anyhow!("row sweep can't handle {tile:?} at {location:?}, use BeamEngine")
+    };
+
+    let mut per_row = vec![0; map.0.len()];
+    for (row_idx, row) in map.0.iter().enumerate().skip(start.0) {
+        if row.len() != width {
+            return Err(anyhow!("row {row_idx} is not as wide as the first row"));
+        }
+        let mut next = vec![0_u128; width];
+        for (col, count) in counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let current = Location(row_idx, col);
+            match map.get(&current) {
+                Tile::Start | Tile::Empty => add_timelines(row_idx, &mut next[col], *count)?,
+                Tile::Splitter => {
+                    for (step, heading) in tachyon_splitter() {
+                        match map.go_direction(&current, &step) {
+                            None => add_timelines(
+                                row_idx,
+                                &mut ended_at_side,
+                                *count * leaves_map_timelines(&step, &heading),
+                            )?,
+                            Some(side) => match map.get(&side) {
+                                Tile::Start | Tile::Empty => {
+                                    add_timelines(row_idx, &mut next[side.1], *count)?
+                                }
+                                Tile::Splitter => {
+                                    return Err(anyhow!(
+                                        "splitters at {current:?} and {side:?} send a beam \
+                                         back and forth forever"
+                                    ));
+                                }
+                                other => return Err(unsupported(other, side)),
+                            },
+                        }
+                    }
+                }
+                other => return Err(unsupported(other, current)),
+            }
+        }
+        counts = next;
+        per_row[row_idx] = counts
+            .iter()
+            .try_fold(ended_at_side, |acc, count| acc.checked_add(*count))
+            .with_context(|| format!("timeline count overflowed in row {row_idx}"))?;
+    }
+
+    Ok(Sweep {
+        timelines: per_row.last().copied().unwrap_or(0),
+        per_row,
+    })
+}
+
+pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u128>, anyhow::Error> {
     let map: Map<Tile> = Map::try_from(input).context("Failed to parse input")?;
     let start = map
         .find(|(_, value)| *value == &Tile::Start)
         .context("expected to find start tile")?;
 
-    let mut cache = map.transform(|_, _| None);
-    Ok(Some(cached_go_path(&map, start, &mut cache)))
+    let sweep = sweep_timelines(&map, &start)?;
+    if std::env::var_os(TIMELINES_ENV).is_some() {
+        for (row, timelines) in sweep.per_row.iter().enumerate().skip(start.0) {
+            println!("row {row:>4}: {timelines}");
+        }
+    }
+
+    Ok(Some(sweep.timelines))
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_7 {
     use super::*;
+    use crate::test_rng::Rng;
+
+    fn tachyon_map(rng: &mut Rng, height: usize, width: usize) -> String {
+        let mut next = || rng.below(100);
+        (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|col| match (row, col) {
+                        (0, col) if col == width / 2 => 'S',
+                        // Splitters on even rows, never next to each other. Every other row
+                        // can have them on the edges, which the real input never does.
+                        (row, col)
+                            if row > 0
+                                && row % 2 == 0
+                                && (col + row / 2) % 2 == 1
+                                && next() < 40 =>
+                        {
+                            '^'
+                        }
+                        _ => '.',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_timelines_match_cached_go_path() -> anyhow::Result<()> {
+        let mut rng = Rng::new(0x2025_0007);
+        let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+        for size in 3..30 {
+            let map: Map<Tile> = Map::try_from(tachyon_map(&mut rng, size, size).as_str())?;
+            let start = Location(0, size / 2);
+            let mut cache = map.transform(|_, _| None);
+            let expected = u128::from(cached_go_path(&map, start.clone(), &mut cache));
+            assert_eq!(
+                sweep_timelines(&map, &start)?.timelines,
+                expected,
+                "size {size}"
+            );
+            assert_eq!(
+                engine.count_timelines(&map, start, Direction::South)?,
+                expected,
+                "size {size}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sweep_timelines() -> anyhow::Result<()> {
+        let input = &advent_of_code::template::read_file_part("examples", DAY, 2);
+        let map: Map<Tile> = Map::try_from(input.as_str())?;
+        let sweep = sweep_timelines(&map, &Location(0, 7))?;
+        assert_eq!(sweep.per_row.len(), 16);
+        assert_eq!(sweep.per_row[..7], [1, 1, 2, 2, 4, 4, 8]);
+        assert_eq!(sweep.timelines, 40);
+
+        // A full pyramid of splitters doubles the timelines on every split row, so 70 rows of
+        // them is more than a u64 can count
+        let depth = 70;
+        let width = 2 * depth + 3;
+        let start = width / 2;
+        let rows: Vec<String> = (0..2 * depth + 2)
+            .map(|row| {
+                (0..width)
+                    .map(|col| {
+                        if row == 0 && col == start {
+                            'S'
+                        } else if row % 2 == 0 && row > 0 && (col + row / 2 + start) % 2 == 1 {
+                            '^'
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let map: Map<Tile> = Map::try_from(rows.join("\n").as_str())?;
+        let sweep = sweep_timelines(&map, &Location(0, start))?;
+        assert_eq!(sweep.timelines, 1 << depth);
+        let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+        assert_eq!(
+            engine.count_timelines(&map, Location(0, start), Direction::South)?,
+            1 << depth
+        );
+
+        let map: Map<Tile> = Map::try_from("S.\n/.")?;
+        assert!(sweep_timelines(&map, &Location(0, 0)).is_err());
+
+        // A beam split off either side of the map is lost, in both counts and the original
+        let map: Map<Tile> = Map::try_from("S..\n...\n^..\n...\n.^.\n...\n..^\n...")?;
+        let sweep = sweep_timelines(&map, &Location(0, 0))?;
+        assert_eq!(sweep.per_row, [1, 1, 1, 1, 2, 2, 2, 2]);
+        assert_eq!(
+            engine.count_timelines(&map, Location(0, 0), Direction::South)?,
+            2
+        );
+        let mut cache = map.transform(|_, _| None);
+        assert_eq!(cached_go_path(&map, Location(0, 0), &mut cache), 2);
+
+        // Splitters next to each other bounce the beam between them forever
+        let map: Map<Tile> = Map::try_from(".S..\n....\n.^^.\n....")?;
+        assert!(sweep_timelines(&map, &Location(0, 1)).is_err());
+        assert!(
+            engine
+                .count_timelines(&map, Location(0, 1), Direction::South)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_mirrors_and_absorbers() -> anyhow::Result<()> {
+        let crossed = |map: &Map<Tile>, seen: &CountingMap| -> String {
+            map.iter()
+                .map(|row| {
+                    row.map(|(location, _)| if seen.get(&location) { '#' } else { '.' })
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        // Beam enters heading east, bounces round the mirrors and is absorbed
+        let map: Map<Tile> = Map::try_from(".\\..\n....\n.\\.#")?;
+        let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+        let seen = engine.trace(&map, Location(0, 0), Direction::East);
+        assert_eq!(crossed(&map, &seen), "##..\n.#..\n.###");
+        assert_eq!(
+            engine.count_timelines(&map, Location(0, 0), Direction::East)?,
+            1
+        );
+
+        // Splitter that sends beams both ways along the row
+        let engine = BeamEngine::new(tile_behaviour(vec![
+            (Direction::East, Direction::East),
+            (Direction::West, Direction::West),
+        ]));
+        let map: Map<Tile> = Map::try_from("..^..\n.....")?;
+        let seen = engine.trace(&map, Location(0, 0), Direction::East);
+        assert_eq!(crossed(&map, &seen), "#####\n.....");
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_loops() -> anyhow::Result<()> {
+        // Mirrors in a square, the beam goes round forever
+        let map: Map<Tile> = Map::try_from("/.\\\n...\n\\./")?;
+        let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+        let seen = engine.trace(&map, Location(0, 1), Direction::East);
+        assert!(
+            [(0, 0), (0, 2), (1, 0), (1, 2), (2, 0), (2, 2)]
+                .iter()
+                .all(|(row, col)| seen.get(&Location(*row, *col)))
+        );
+        assert!(!seen.get(&Location(1, 1)));
+        assert!(
+            engine
+                .count_timelines(&map, Location(0, 1), Direction::East)
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_overlay() -> anyhow::Result<()> {
+        let map: Map<Tile> = Map::try_from("..S..\n.....\n..^..\n.....\n^...^")?;
+        let engine = BeamEngine::new(tile_behaviour(tachyon_splitter()));
+        let seen = engine.trace(&map, Location(0, 2), Direction::South);
+
+        let hit = "\x1b[1;32m^\x1b[0m";
+        let missed = "\x1b[1;31m^\x1b[0m";
+        assert_eq!(
+            render_overlay(&map, &seen),
+            format!("..S..\n..|..\n.|{hit}|.\n.|.|.\n{missed}|.|{missed}\n")
+        );
+
+        let svg = render_svg(&map, &seen)?;
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        // Column 2 is one run from the start down to the splitter, 1 and 3 from the splitter down
+        assert!(svg.contains("<rect x=\"2\" y=\"0\" width=\"1\" height=\"3\" fill=\"#ffff66\"/>"));
+        assert!(svg.contains("<rect x=\"1\" y=\"2\" width=\"1\" height=\"3\" fill=\"#ffff66\"/>"));
+        assert_eq!(svg.matches("#ffff66").count(), 3);
+        assert_eq!(svg.matches("#00cc00").count(), 1);
+        assert_eq!(svg.matches("#ff0000").count(), 2);
+        Ok(())
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
@@ -2,12 +2,13 @@ advent_of_code::solution!(8);
 
 use advent_of_code::template::RunType;
 use aoc_lib::parse::preamble::*;
-use std::collections::{BTreeMap, BTreeSet};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 
 use anyhow::{Context, Result, anyhow};
 
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd)]
-struct Point {
+pub struct Point {
     x: u64,
     y: u64,
     z: u64,
@@ -23,13 +24,17 @@ impl Point {
 
         out
     }
+
+    fn axis(&self, axis: usize) -> u64 {
+        match axis {
+            0 => self.x,
+            1 => self.y,
+            _ => self.z,
+        }
+    }
 }
 
-pub fn part_one(input: &str, run_type: RunType) -> Result<Option<usize>, anyhow::Error> {
-    let max_connections = match run_type {
-        RunType::Example => 10,
-        RunType::Real => 1000,
-    };
+fn parse_points(input: &str) -> Result<Vec<Point>> {
     let raw: Vec<(u64, u64, u64)> = parse_input(
         LineSplitter,
         ParseTuple3(ParseFromStr, ParseFromStr, ParseFromStr, ","),
@@ -39,68 +44,235 @@ pub fn part_one(input: &str, run_type: RunType) -> Result<Option<usize>, anyhow:
 
     let mut points: Vec<Point> = raw.into_iter().map(|(x, y, z)| Point { x, y, z }).collect();
     points.sort();
+    Ok(points)
+}
 
+// Every pair as (smaller index, larger index, distance), closest first. Only used to cross-check
+// NearestPairs and as the baseline in the benchmark.
+#[cfg(test)]
+fn all_pairs(points: &[Point]) -> Vec<(usize, usize, u64)> {
     let mut dists = Vec::new();
-    for a in points.iter() {
-        for b in points.iter() {
-            if a <= b {
-                break;
+    for (a_idx, a) in points.iter().enumerate() {
+        for (b_idx, b) in points[..a_idx].iter().enumerate() {
+            dists.push((b_idx, a_idx, a.dist_relative(b)));
+        }
+    }
+    dists.sort_by_key(|(_, _, d)| *d);
+    dists
+}
+
+// k-d tree over point indices. The tree is implicit: the median of each slice is its root and
+// the halves either side are its subtrees, splitting on x, y and z in turn.
+pub struct KdTree<'a> {
+    points: &'a [Point],
+    nodes: Vec<usize>,
+}
+
+impl<'a> KdTree<'a> {
+    pub fn new(points: &'a [Point]) -> Self {
+        fn build(points: &[Point], nodes: &mut [usize], depth: usize) {
+            if nodes.len() <= 1 {
+                return;
             }
-            dists.push((b, a, a.dist_relative(b)));
+            let mid = nodes.len() / 2;
+            nodes.select_nth_unstable_by_key(mid, |idx| points[*idx].axis(depth % 3));
+            let (left, right) = nodes.split_at_mut(mid);
+            build(points, left, depth + 1);
+            build(points, &mut right[1..], depth + 1);
         }
+
+        let mut nodes: Vec<usize> = (0..points.len()).collect();
+        build(points, &mut nodes, 0);
+        Self { points, nodes }
     }
 
-    dists.sort_by_key(|(_, _, d)| *d);
+    // The `k` points closest to points[target], not including itself, as (distance, index)
+    // closest first. Ties are broken by index so a bigger `k` only ever adds to the end.
+    pub fn nearest(&self, target: usize, k: usize) -> Vec<(u64, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(&self.nodes, 0, target, k, &mut best);
+        }
+        best.into_sorted_vec()
+    }
 
-    let mut connections = 0;
-    let mut next_id = 0;
-    let mut circuits = BTreeMap::new();
-    let mut point_to_circuit: BTreeMap<Point, usize> = BTreeMap::new();
-    for (a, b, _) in dists {
-        connections += 1;
-        if connections > max_connections {
-            break;
+    fn search(
+        &self,
+        nodes: &[usize],
+        depth: usize,
+        target: usize,
+        k: usize,
+        best: &mut BinaryHeap<(u64, usize)>,
+    ) {
+        if nodes.is_empty() {
+            return;
         }
-        match (point_to_circuit.get(a), point_to_circuit.get(b)) {
-            // New circuit!
-            (None, None) => {
-                point_to_circuit.insert(a.clone(), next_id);
-                point_to_circuit.insert(b.clone(), next_id);
-
-                let mut circuit = BTreeSet::new();
-                circuit.insert(a.clone());
-                circuit.insert(b.clone());
-                circuits.insert(next_id, circuit);
-                next_id += 1;
-            }
-            // One missing
-            (Some(idx), None) => {
-                circuits.get_mut(idx).unwrap().insert(b.clone());
-                point_to_circuit.insert(b.clone(), *idx);
+        let mid = nodes.len() / 2;
+        let idx = nodes[mid];
+        let (point, from) = (&self.points[idx], &self.points[target]);
+
+        if idx != target {
+            best.push((from.dist_relative(point), idx));
+            if best.len() > k {
+                best.pop();
             }
-            (None, Some(idx)) => {
-                circuits.get_mut(idx).unwrap().insert(a.clone());
-                point_to_circuit.insert(a.clone(), *idx);
+        }
+
+        let axis = depth % 3;
+        let (near, far) = if from.axis(axis) < point.axis(axis) {
+            (&nodes[..mid], &nodes[mid + 1..])
+        } else {
+            (&nodes[mid + 1..], &nodes[..mid])
+        };
+        self.search(near, depth + 1, target, k, best);
+
+        // Anything on the far side is at least as far away as the splitting plane
+        let plane = from.axis(axis).abs_diff(point.axis(axis)).pow(2);
+        if best.len() < k || best.peek().is_some_and(|(worst, _)| plane <= *worst) {
+            self.search(far, depth + 1, target, k, best);
+        }
+    }
+}
+
+// Neighbours fetched per point the first time, doubled whenever they run out.
+const INITIAL_NEIGHBOURS: usize = 8;
+
+// Every pair of points as (smaller index, larger index, distance) in the same order as
+// all_pairs, produced lazily. Each point keeps a queue of its neighbours with a smaller index,
+// and a heap holds the next pair from every queue, so only the pairs that are asked for (plus a
+// few nearest neighbours each) are ever worked out.
+pub struct NearestPairs<'a> {
+    tree: KdTree<'a>,
+    found: Vec<Vec<(u64, usize)>>,
+    taken: Vec<usize>,
+    queried: Vec<usize>,
+    heap: BinaryHeap<Reverse<(u64, usize, usize)>>,
+}
+
+impl<'a> NearestPairs<'a> {
+    pub fn new(points: &'a [Point]) -> Self {
+        let mut out = Self {
+            tree: KdTree::new(points),
+            found: vec![Vec::new(); points.len()],
+            taken: vec![0; points.len()],
+            queried: vec![0; points.len()],
+            heap: BinaryHeap::new(),
+        };
+        for idx in 0..points.len() {
+            out.queue_next(idx);
+        }
+        out
+    }
+
+    fn queue_next(&mut self, idx: usize) {
+        loop {
+            if let Some((dist, other)) = self.found[idx].get(self.taken[idx]) {
+                self.taken[idx] += 1;
+                self.heap.push(Reverse((*dist, idx, *other)));
+                return;
             }
-            // Merge circuits
-            (Some(a_idx), Some(b_idx)) => {
-                if a_idx == b_idx {
-                    continue;
-                }
-                let mut c1 = circuits.remove(a_idx).unwrap();
-                let mut c2 = circuits.remove(b_idx).unwrap();
-
-                c1.append(&mut c2);
-                for p in c1.iter() {
-                    point_to_circuit.insert(p.clone(), next_id);
-                }
-                circuits.insert(next_id, c1);
-                next_id += 1;
+            // Only `idx` points have a smaller index
+            let all = self.tree.points.len() - 1;
+            if self.taken[idx] == idx || self.queried[idx] == all {
+                return;
             }
+            self.queried[idx] = std::cmp::min(
+                std::cmp::max(self.queried[idx] * 2, INITIAL_NEIGHBOURS),
+                all,
+            );
+            self.found[idx] = self
+                .tree
+                .nearest(idx, self.queried[idx])
+                .into_iter()
+                .filter(|(_, other)| *other < idx)
+                .collect();
+        }
+    }
+}
+
+impl Iterator for NearestPairs<'_> {
+    type Item = (usize, usize, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((dist, idx, other)) = self.heap.pop()?;
+        self.queue_next(idx);
+        Some((other, idx, dist))
+    }
+}
+
+// Disjoint sets over 0..n, merged by size with path compression so every operation is close to
+// constant time.
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    pub fn new(count: usize) -> Self {
+        Self {
+            parent: (0..count).collect(),
+            size: vec![1; count],
+            components: count,
+        }
+    }
+
+    pub fn find(&mut self, item: usize) -> usize {
+        let mut root = item;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+        // Point everything on the way straight at the root
+        let mut current = item;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
         }
+        root
     }
 
-    let mut lengths: Vec<usize> = circuits.values().map(|points| points.len()).collect();
+    // Returns false if they were already in the same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+        true
+    }
+
+    // Size of every set, including the ones with a single item, in no particular order.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        (0..self.parent.len())
+            .filter(|idx| self.parent[*idx] == *idx)
+            .map(|idx| self.size[idx])
+            .collect()
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+}
+
+pub fn part_one(input: &str, run_type: RunType) -> Result<Option<usize>, anyhow::Error> {
+    let max_connections = match run_type {
+        RunType::Example => 10,
+        RunType::Real => 1000,
+    };
+    let points = parse_points(input)?;
+
+    let mut circuits = UnionFind::new(points.len());
+    for (a, b, _) in NearestPairs::new(&points).take(max_connections) {
+        circuits.union(a, b);
+    }
+
+    let mut lengths = circuits.component_sizes();
     lengths.sort();
     lengths.reverse();
 
@@ -110,78 +282,24 @@ pub fn part_one(input: &str, run_type: RunType) -> Result<Option<usize>, anyhow:
 }
 
 pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::Error> {
-    let raw: Vec<(u64, u64, u64)> = parse_input(
-        LineSplitter,
-        ParseTuple3(ParseFromStr, ParseFromStr, ParseFromStr, ","),
-        input,
-    )
-    .context("failed to parse input")?;
-
-    let mut points: Vec<Point> = raw.into_iter().map(|(x, y, z)| Point { x, y, z }).collect();
-    points.sort();
-
-    let mut dists = Vec::new();
-    for a in points.iter() {
-        for b in points.iter() {
-            if a <= b {
-                break;
-            }
-            dists.push((b, a, a.dist_relative(b)));
-        }
-    }
-
-    dists.sort_by_key(|(_, _, d)| *d);
+    let points = parse_points(input)?;
 
     let mut last_points = None;
-    let mut next_id = 0;
-    let mut circuits = BTreeMap::new();
-    let mut point_to_circuit: BTreeMap<Point, usize> = BTreeMap::new();
-    for (a, b, _) in dists {
-        if point_to_circuit.len() == points.len() && circuits.len() == 1 {
+    let mut circuits = UnionFind::new(points.len());
+    for (a, b, _) in NearestPairs::new(&points) {
+        if circuits.component_count() == 1 {
             break;
         }
-        last_points = Some((a.clone(), b.clone()));
-        match (point_to_circuit.get(a), point_to_circuit.get(b)) {
-            // New circuit!
-            (None, None) => {
-                point_to_circuit.insert(a.clone(), next_id);
-                point_to_circuit.insert(b.clone(), next_id);
-
-                let mut circuit = BTreeSet::new();
-                circuit.insert(a.clone());
-                circuit.insert(b.clone());
-                circuits.insert(next_id, circuit);
-                next_id += 1;
-            }
-            // One missing
-            (Some(idx), None) => {
-                circuits.get_mut(idx).unwrap().insert(b.clone());
-                point_to_circuit.insert(b.clone(), *idx);
-            }
-            (None, Some(idx)) => {
-                circuits.get_mut(idx).unwrap().insert(a.clone());
-                point_to_circuit.insert(a.clone(), *idx);
-            }
-            // Merge circuits
-            (Some(a_idx), Some(b_idx)) => {
-                if a_idx == b_idx {
-                    continue;
-                }
-                let mut c1 = circuits.remove(a_idx).unwrap();
-                let mut c2 = circuits.remove(b_idx).unwrap();
-
-                c1.append(&mut c2);
-                for p in c1.iter() {
-                    point_to_circuit.insert(p.clone(), next_id);
-                }
-                circuits.insert(next_id, c1);
-                next_id += 1;
-            }
+        if circuits.union(a, b) {
+            last_points = Some((&points[a], &points[b]));
         }
     }
 
-    if circuits.len() != 1 {
-        return Err(anyhow!("Expected single circuit found {}", circuits.len()));
+    if circuits.component_count() != 1 {
+        return Err(anyhow!(
+            "Expected single circuit found {}",
+            circuits.component_count()
+        ));
     }
 
     match last_points {
@@ -190,9 +308,159 @@ pub fn part_two(input: &str, _run_type: RunType) -> Result<Option<u64>, anyhow::
     }
 }
 
+#[cfg(test)]
+#[path = "../test_rng.rs"]
+mod test_rng;
+
 #[cfg(test)]
 mod tests_day_8 {
     use super::*;
+    use crate::test_rng::Rng;
+    use itertools::Itertools;
+
+    fn random_points(count: usize, range: u64, seed: u64) -> Vec<Point> {
+        let mut rng = Rng::new(seed);
+        let mut next = || rng.below(range);
+        let mut points: Vec<Point> = (0..count)
+            .map(|_| Point {
+                x: next(),
+                y: next(),
+                z: next(),
+            })
+            .collect();
+        points.sort();
+        points
+    }
+
+    #[test]
+    fn test_nearest_pairs_matches_all_pairs() {
+        // Small ranges give plenty of ties and duplicate points
+        for (count, range) in [
+            (0, 10),
+            (1, 10),
+            (2, 10),
+            (50, 4),
+            (200, 20),
+            (300, 100_000),
+        ] {
+            let points = random_points(count, range, 0x2025_0008 + count as u64);
+            let expected = all_pairs(&points);
+            let actual: Vec<_> = NearestPairs::new(&points).collect();
+            assert_eq!(actual, expected, "count={count} range={range}");
+
+            let tree = KdTree::new(&points);
+            for target in 0..count {
+                let mut brute: Vec<(u64, usize)> = (0..count)
+                    .filter(|idx| *idx != target)
+                    .map(|idx| (points[target].dist_relative(&points[idx]), idx))
+                    .collect();
+                brute.sort();
+                brute.truncate(5);
+                assert_eq!(tree.nearest(target, 5), brute);
+            }
+        }
+    }
+
+    // Above this the all-pairs baseline needs too much memory to be worth running
+    const ALL_PAIRS_LIMIT: usize = 5000;
+
+    // cargo test --release --bin 08 -- --ignored --nocapture
+    // AOC_DAY8_BENCH_SIZE can be used to set the largest number of points.
+    #[test]
+    #[ignore]
+    fn bench_nearest_pairs() {
+        let max_size = std::env::var("AOC_DAY8_BENCH_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(64_000);
+        let mut size = 1000;
+        while size <= max_size {
+            let points = random_points(size, 100_000, 0x2025_0024);
+            // Roughly what part one and part two need
+            for edges in [1000, 4 * size] {
+                let start = std::time::Instant::now();
+                let lazy: Vec<_> = NearestPairs::new(&points).take(edges).collect();
+                let lazy_time = start.elapsed();
+
+                let baseline = if size <= ALL_PAIRS_LIMIT {
+                    let start = std::time::Instant::now();
+                    let mut all = all_pairs(&points);
+                    all.truncate(edges);
+                    let all_time = start.elapsed();
+                    assert_eq!(lazy, all);
+                    format!("{all_time:?}")
+                } else {
+                    "skipped".to_string()
+                };
+                println!(
+                    "{size} points, first {edges} pairs: k-d tree {lazy_time:?}, all pairs {baseline}"
+                );
+            }
+            size *= 2;
+        }
+    }
+
+    #[test]
+    fn test_union_find() {
+        let mut circuits = UnionFind::new(6);
+        assert_eq!(circuits.component_count(), 6);
+        assert!(circuits.union(0, 1));
+        assert!(circuits.union(2, 3));
+        assert!(circuits.union(1, 3));
+        assert!(!circuits.union(0, 2));
+        assert_eq!(circuits.find(0), circuits.find(3));
+        assert_ne!(circuits.find(0), circuits.find(4));
+        assert_eq!(circuits.component_count(), 3);
+
+        let mut sizes = circuits.component_sizes();
+        sizes.sort();
+        assert_eq!(sizes, vec![1, 1, 4]);
+        assert_eq!(UnionFind::new(0).component_sizes(), Vec::<usize>::new());
+    }
+
+    // Relabels a whole circuit on every merge, the same as the old BTreeMap approach
+    fn circuits_by_relabelling(count: usize, pairs: &[(usize, usize, u64)]) -> Vec<Vec<usize>> {
+        let mut label: Vec<usize> = (0..count).collect();
+        let mut history = Vec::new();
+        for (a, b, _) in pairs {
+            let (from, to) = (label[*b], label[*a]);
+            for l in label.iter_mut().filter(|l| **l == from) {
+                *l = to;
+            }
+            history.push(label.clone());
+        }
+        history
+    }
+
+    #[test]
+    fn test_union_find_matches_relabelling() {
+        let points = random_points(60, 1000, 0x2025_0025);
+        let pairs = all_pairs(&points);
+        let history = circuits_by_relabelling(points.len(), &pairs[..300]);
+
+        let mut circuits = UnionFind::new(points.len());
+        for ((a, b, _), labels) in pairs.iter().zip(history) {
+            circuits.union(*a, *b);
+
+            let mut expected: Vec<usize> = labels
+                .iter()
+                .sorted()
+                .dedup_with_count()
+                .map(|(size, _)| size)
+                .collect();
+            expected.sort();
+            let mut sizes = circuits.component_sizes();
+            sizes.sort();
+            assert_eq!(sizes, expected);
+            assert_eq!(circuits.component_count(), expected.len());
+            for idx in 0..points.len() {
+                assert_eq!(
+                    circuits.find(idx) == circuits.find(*a),
+                    labels[idx] == labels[*a]
+                );
+            }
+        }
+    }
 
     #[test]
     fn test_part_one() -> anyhow::Result<()> {
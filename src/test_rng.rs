@@ -0,0 +1,29 @@
+// Small seeded generator for the randomised tests in src/bin, so every run sees the same inputs
+// without pulling in another crate. Each day includes it with
+// `#[cfg(test)] #[path = "../test_rng.rs"] mod test_rng;`.
+
+// Not every day uses every method
+#![allow(dead_code)]
+
+// splitmix64, every bit of the output is usable so callers can take whichever bits they want.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    // Value in 0..n, the modulo bias doesn't matter for tests.
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}